@@ -1,41 +1,281 @@
-use std::{fs::OpenOptions, io::Result, io::Write, process::Command};
+use std::{
+    collections::HashMap,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+    process::{Command, ExitStatus},
+};
 
-pub fn add(_crate: &str, path: &str) -> Result<()> {
-    Command::new("cargo")
-        .args(["add", _crate])
-        .current_dir(path)
-        .status()?;
-    Ok(())
+use crate::config::Crate;
+
+/// A cargo command that failed.
+#[derive(Debug)]
+pub enum CargoError {
+    /// The command could not be run, such as when cargo is not installed.
+    Spawn { command: String, error: io::Error },
+    /// The command exited with a failure status.
+    Failed {
+        command: String,
+        status: ExitStatus,
+        stderr: String,
+    },
+    /// The output of the command could not be parsed.
+    InvalidOutput {
+        command: String,
+        error: serde_json::Error,
+    },
 }
 
-pub fn new_lib(name: &str, path: &str) -> Result<()> {
-    Command::new("cargo")
-        .args(["new", "--lib", name])
-        .current_dir(path)
-        .status()?;
+impl fmt::Display for CargoError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CargoError::Spawn { command, error } => {
+                write!(f, "could not run `{}`: {}", command, error)
+            }
+            CargoError::Failed {
+                command,
+                status,
+                stderr,
+            } => {
+                write!(f, "`{}` failed with {}", command, status)?;
+                if !stderr.is_empty() {
+                    write!(f, ":\n{}", stderr)?;
+                }
+                Ok(())
+            }
+            CargoError::InvalidOutput { command, error } => {
+                write!(f, "could not parse the output of `{}`: {}", command, error)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CargoError {}
+
+/// The version of jni the generated code is written for.
+const JNI_VERSION: &str = "0.21.1";
+
+/// Creates the cargo lib `name` in the directory at `path`, depending on jni and the crates of
+/// the config. The paths of crates are relative to `config_dir`, which is absolute.
+pub fn new_lib(name: &str, path: &Path, config: &[Crate], config_dir: &Path) -> io::Result<()> {
+    fs::create_dir_all(path.join("src"))?;
+    // The lib is generated later, but cargo needs its source to read the metadata
+    fs::write(path.join("src/lib.rs"), "")?;
+    fs::write(
+        path.join("Cargo.toml"),
+        get_manifest(name, config, config_dir),
+    )?;
+    fs::write(path.join(".gitignore"), "/target\n")
+}
+
+fn get_manifest(name: &str, config: &[Crate], config_dir: &Path) -> String {
+    let mut dependencies = vec![format!("jni = {}", toml_string(JNI_VERSION))];
+    for package in config {
+        dependencies.push(format!(
+            "{} = {}",
+            package.name,
+            get_dependency(package, config_dir)
+        ));
+    }
+    format!(
+        r#"[package]
+name = {}
+version = "0.1.0"
+edition = "2024"
+
+[lib]
+crate-type = ["cdylib"]
+
+[dependencies]
+{}
+"#,
+        toml_string(name),
+        dependencies.join("\n")
+    )
+}
 
-    let mut file = OpenOptions::new()
-        .append(true)
-        .open(format!("{}/{}/Cargo.toml", path, name))?;
+/// The inline table of the dependency on a crate, which is any version of it from crates.io
+/// unless the config gives a version, path or git repository.
+fn get_dependency(package: &Crate, config_dir: &Path) -> String {
+    let mut fields = Vec::new();
+    if let Some(version) = &package.version {
+        fields.push(format!("version = {}", toml_string(version)));
+    }
+    if let Some(path) = &package.path {
+        let path = config_dir.join(path);
+        fields.push(format!("path = {}", toml_string(&path.to_string_lossy())));
+    }
+    if let Some(git) = &package.git {
+        fields.push(format!("git = {}", toml_string(git)));
+    }
+    if fields.is_empty() {
+        fields.push(format!("version = {}", toml_string("*")));
+    }
+    if let Some(features) = &package.features {
+        let features = features.iter().map(|f| toml_string(f)).collect::<Vec<_>>();
+        fields.push(format!("features = [{}]", features.join(", ")));
+    }
+    if let Some(default_features) = package.default_features {
+        fields.push(format!("default-features = {}", default_features));
+    }
+    format!("{{ {} }}", fields.join(", "))
+}
 
-    writeln!(file, "\n[lib]")?;
-    writeln!(file, "crate-type = [\"cdylib\"]")?;
+/// A TOML string, escaped as a JSON string, since JSON escapes are also valid in TOML.
+fn toml_string(value: &str) -> String {
+    serde_json::to_string(value).unwrap()
+}
 
+pub fn clippy_fix(path: &Path) -> Result<(), CargoError> {
+    run(
+        &["clippy", "--fix", "--allow-dirty", "--allow-no-vcs"],
+        path,
+    )?;
     Ok(())
 }
 
-pub fn clippy_fix(path: &str) -> Result<()> {
-    Command::new("cargo")
-        .args(["clippy", "--fix", "--allow-dirty"])
-        .current_dir(path)
-        .status()?;
+pub fn format(path: &Path) -> Result<(), CargoError> {
+    run(&["fmt"], path)?;
     Ok(())
 }
 
-pub fn format(path: &str) -> Result<()> {
-    Command::new("cargo")
-        .args(["fmt"])
+/// Finds the root source files of the libraries of the lib and its dependencies, such as
+/// `src/lib.rs`, by package name.
+pub fn find_lib_sources(path: &Path) -> Result<HashMap<String, PathBuf>, CargoError> {
+    let args = ["metadata", "--format-version", "1"];
+    let output = run(&args, path)?;
+    let metadata: serde_json::Value =
+        serde_json::from_slice(&output).map_err(|e| CargoError::InvalidOutput {
+            command: get_command(&args),
+            error: e,
+        })?;
+
+    Ok(get_lib_sources(&metadata))
+}
+
+fn get_lib_sources(metadata: &serde_json::Value) -> HashMap<String, PathBuf> {
+    let mut sources = HashMap::new();
+    for package in metadata["packages"].as_array().into_iter().flatten() {
+        let source = package["targets"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .find(|t| {
+                t["kind"]
+                    .as_array()
+                    .is_some_and(|kinds| kinds.iter().any(|k| k == "lib"))
+            })
+            .and_then(|t| t["src_path"].as_str());
+        if let (Some(name), Some(source)) = (package["name"].as_str(), source) {
+            sources.insert(name.to_string(), PathBuf::from(source));
+        }
+    }
+    sources
+}
+
+/// Runs cargo with `args` in the directory at `path`, returning its standard output.
+fn run(args: &[&str], path: &Path) -> Result<Vec<u8>, CargoError> {
+    let output = Command::new("cargo")
+        .args(args)
         .current_dir(path)
-        .status()?;
-    Ok(())
+        .output()
+        .map_err(|e| CargoError::Spawn {
+            command: get_command(args),
+            error: e,
+        })?;
+    if !output.status.success() {
+        return Err(CargoError::Failed {
+            command: get_command(args),
+            status: output.status,
+            stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        });
+    }
+    Ok(output.stdout)
+}
+
+fn get_command(args: &[&str]) -> String {
+    format!("cargo {}", args.join(" "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::parse;
+
+    #[test]
+    fn test_get_manifest() {
+        let config = parse(
+            r#"[
+                {"name": "any-version", "members": []},
+                {"name": "versioned", "version": "1.2", "members": []},
+                {"name": "local", "path": "../local", "members": []},
+                {
+                    "name": "remote",
+                    "git": "https://example.com/remote.git",
+                    "features": ["serde", "std"],
+                    "default_features": false,
+                    "members": []
+                }
+            ]"#,
+        )
+        .unwrap();
+        assert_eq!(
+            get_manifest("mylib", &config, Path::new("/config")),
+            r#"[package]
+name = "mylib"
+version = "0.1.0"
+edition = "2024"
+
+[lib]
+crate-type = ["cdylib"]
+
+[dependencies]
+jni = "0.21.1"
+any-version = { version = "*" }
+versioned = { version = "1.2" }
+local = { path = "/config/../local" }
+remote = { git = "https://example.com/remote.git", features = ["serde", "std"], default-features = false }
+"#
+        );
+    }
+
+    #[test]
+    fn test_get_lib_sources() {
+        let metadata = serde_json::json!({
+            "packages": [
+                {
+                    "name": "my-crate",
+                    "targets": [
+                        {"kind": ["bin"], "src_path": "/my-crate/src/main.rs"},
+                        {"kind": ["lib"], "src_path": "/my-crate/src/lib.rs"}
+                    ]
+                },
+                {"name": "tool", "targets": [{"kind": ["bin"], "src_path": "/tool/src/main.rs"}]}
+            ]
+        });
+        assert_eq!(
+            get_lib_sources(&metadata),
+            HashMap::from([(
+                "my-crate".to_string(),
+                PathBuf::from("/my-crate/src/lib.rs")
+            )])
+        );
+    }
+
+    #[test]
+    fn test_run_reports_failures() {
+        let error = run(&["no-such-command"], Path::new(".")).unwrap_err();
+        let CargoError::Failed {
+            command, stderr, ..
+        } = &error
+        else {
+            panic!("Unexpected error {:?}", error);
+        };
+        assert_eq!(command, "cargo no-such-command");
+        assert!(stderr.contains("no-such-command"));
+        assert!(
+            error
+                .to_string()
+                .starts_with("`cargo no-such-command` failed with")
+        );
+    }
 }
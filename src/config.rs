@@ -1,35 +1,551 @@
+use std::collections::HashMap;
+
+use schemars::{JsonSchema, Schema, schema_for};
 use serde::Deserialize;
-use serde_json::from_str;
+use serde_json::{Value, from_str, json};
+
+use crate::{
+    error::Diagnostic,
+    names::{is_rust_identifier, to_camel_case, to_pascal_case},
+    rust_type::{RustType, TypeError, parse_type},
+};
 
-#[derive(Deserialize)]
+#[derive(Deserialize, JsonSchema, Clone)]
+#[serde(deny_unknown_fields)]
 pub struct Input {
+    /// The Rust type of the parameter, such as `&[f32]`.
     #[serde(rename = "type")]
-    pub rust_type: String,
+    pub rust_type: RustType,
+    /// The name of the Rust parameter.
     pub name: String,
+    /// Overrides the name of the Kotlin parameter.
+    pub kotlin_name: Option<String>,
+    /// Documents the parameter on the Kotlin function and the Rust wrapper.
+    pub docs: Option<String>,
+    /// The default value of the Kotlin parameter, as a JSON literal of its type.
+    pub default: Option<serde_json::Value>,
 }
 
-#[derive(Deserialize)]
-pub struct Member {
-    #[serde(rename = "type")]
-    pub member_type: String,
+/// An item of a crate to bind, tagged by its `type`, such as `"type": "function"`.
+#[derive(Deserialize, JsonSchema, Clone)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum Member {
+    Function(Function),
+    // Structs, enums and constants are not bound yet, and are reported by the validation
+    Struct(Item),
+    Enum(Item),
+    Const(Item),
+}
+
+impl Member {
+    pub fn name(&self) -> &str {
+        match self {
+            Member::Function(function) => &function.name,
+            Member::Struct(item) | Member::Enum(item) | Member::Const(item) => &item.name,
+        }
+    }
+
+    /// The `type` of the member in the config.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Member::Function(_) => "function",
+            Member::Struct(_) => "struct",
+            Member::Enum(_) => "enum",
+            Member::Const(_) => "const",
+        }
+    }
+}
+
+/// A member that is not a function, which only has a name until it can be bound.
+#[derive(Deserialize, JsonSchema, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Item {
+    /// The full path of the member, such as `my_crate::geo::Point`.
     pub name: String,
-    pub inputs: Option<Vec<Input>>,
-    pub output: Option<String>,
 }
 
-#[derive(Deserialize)]
+/// A function of the crate, bound to a Kotlin function.
+#[derive(Deserialize, JsonSchema, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Function {
+    /// The full path of the function, such as `my_crate::geo::distance`.
+    pub name: String,
+    #[serde(default)]
+    pub inputs: Vec<Input>,
+    /// The Rust type of the output, if the function returns a value. The `Err` value of a `Result`
+    /// is thrown as an exception.
+    pub output: Option<RustType>,
+    /// The generic type parameters of a function, in declaration order.
+    pub generics: Option<Vec<String>>,
+    /// The concrete types to generate a binding for, for each generic type parameter.
+    pub instantiations: Option<Vec<HashMap<String, RustType>>>,
+    /// Whether this function is deliberately bound to the same Kotlin name as other functions.
+    pub overload: Option<bool>,
+    /// Whether the panics of this function are thrown as exceptions, which also generates a
+    /// variant returning a `Result` when the crate has Kotlin wrappers. The errors of a `Result`
+    /// output are thrown whether or not the function is fallible.
+    pub fallible: Option<bool>,
+    /// Overrides the name of the Kotlin function.
+    pub kotlin_name: Option<String>,
+    /// Overrides the object the Kotlin function is declared in, as a dot separated path of
+    /// objects nested in the root object. An empty path declares it in the root object.
+    pub kotlin_object: Option<String>,
+    /// Documents the function as KDoc and on the Rust wrapper, in place of the docs in the source.
+    pub docs: Option<String>,
+}
+
+impl Function {
+    /// Creates a function for each instantiation of a generic function, with the generic
+    /// parameters replaced by the concrete types. A monomorphized function keeps its single
+    /// instantiation so that it can be called with a turbofish.
+    pub fn monomorphize(&self) -> Result<Vec<Function>, TypeError> {
+        let Some(generics) = self.generics.as_ref().filter(|g| !g.is_empty()) else {
+            return Ok(vec![self.clone()]);
+        };
+
+        let mut functions = Vec::new();
+        for bindings in self.instantiations.as_ref().unwrap_or(&vec![]) {
+            if let Some(generic) = generics.iter().find(|g| !bindings.contains_key(*g)) {
+                return Err(TypeError {
+                    rust_type: generic.clone(),
+                    reason: format!(
+                        "no concrete type is given for it in an instantiation of `{}`",
+                        self.name
+                    ),
+                });
+            }
+
+            // Parse the substituted types again so that they are validated
+            let substitute =
+                |rust_type: &RustType| parse_type(&rust_type.substitute(bindings).to_string());
+
+            let mut inputs = Vec::new();
+            for input in &self.inputs {
+                inputs.push(Input {
+                    rust_type: substitute(&input.rust_type)?,
+                    ..input.clone()
+                });
+            }
+
+            functions.push(Function {
+                inputs,
+                output: self.output.as_ref().map(substitute).transpose()?,
+                instantiations: Some(vec![bindings.clone()]),
+                ..self.clone()
+            });
+        }
+        Ok(functions)
+    }
+
+    /// The type of the value returned to Java, which is the `Ok` type of a `Result` output.
+    pub fn returned_type(&self) -> Option<&RustType> {
+        match self.output.as_ref()? {
+            RustType::Result(ok, _) => ok.as_deref(),
+            output => Some(output),
+        }
+    }
+
+    /// Whether the function throws exceptions, for its panics if it is fallible or for the errors
+    /// it returns.
+    pub fn throws(&self) -> bool {
+        self.fallible == Some(true) || matches!(self.output, Some(RustType::Result(..)))
+    }
+
+    /// The concrete types of a monomorphized function, in the order of its generic parameters.
+    pub fn type_arguments(&self) -> Option<Vec<&RustType>> {
+        let generics = self.generics.as_ref().filter(|g| !g.is_empty())?;
+        let bindings = self.instantiations.as_ref()?.first()?;
+        generics.iter().map(|g| bindings.get(g)).collect()
+    }
+}
+
+/// How Rust names are converted to Kotlin names.
+#[derive(Deserialize, JsonSchema, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Naming {
+    /// Words that are kept upper case, such as `GPS`.
+    pub acronyms: Option<Vec<String>>,
+    /// Prefixes removed from function names, such as `ffi_`.
+    pub strip_prefixes: Option<Vec<String>>,
+    /// Replacements for Kotlin names, such as `{"fun": "function"}` to avoid a keyword.
+    pub renames: Option<HashMap<String, String>>,
+}
+
+impl Naming {
+    pub fn to_pascal_case(&self, name: &str) -> String {
+        to_pascal_case(name, self.acronyms.as_deref().unwrap_or_default())
+    }
+
+    pub fn to_camel_case(&self, name: &str) -> String {
+        to_camel_case(name, self.acronyms.as_deref().unwrap_or_default())
+    }
+
+    /// Replaces a Kotlin name if it has a rename.
+    pub fn rename(&self, name: &str) -> String {
+        self.renames
+            .as_ref()
+            .and_then(|renames| renames.get(name))
+            .cloned()
+            .unwrap_or_else(|| name.to_string())
+    }
+
+    /// Removes the first matching prefix from a function name.
+    pub fn strip_prefix<'a>(&self, name: &'a str) -> &'a str {
+        self.strip_prefixes
+            .as_deref()
+            .unwrap_or_default()
+            .iter()
+            .find_map(|prefix| name.strip_prefix(prefix.as_str()))
+            .filter(|stripped| !stripped.is_empty())
+            .unwrap_or(name)
+    }
+}
+
+#[derive(Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
 pub struct Crate {
+    /// The name of the crate, such as `my-crate`.
     pub name: String,
+    /// The version requirement of the crate dependency, such as `"1.2"`.
+    pub version: Option<String>,
+    /// The path of the crate, relative to the config.
+    pub path: Option<String>,
+    /// The git repository of the crate.
+    pub git: Option<String>,
+    /// The features of the crate to enable.
+    pub features: Option<Vec<String>>,
+    /// Whether to enable the default features of the crate.
+    pub default_features: Option<bool>,
+    pub naming: Option<Naming>,
+    /// Whether to generate public Kotlin wrappers of private external functions.
+    pub kotlin_wrappers: Option<bool>,
+    /// The members of the crate to bind.
     pub members: Vec<Member>,
 }
 
-pub fn parse(json: &str) -> Result<Vec<Crate>, serde_json::Error> {
-    from_str(json)
+impl Crate {
+    /// Finds the problems of the members and dependency of the crate, which are checked before
+    /// the types and Kotlin names of its functions.
+    pub fn validate(&self) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+        if self.path.is_some() && self.git.is_some() {
+            diagnostics.push(Diagnostic::error(
+                "both a `path` and a `git` repository are given; give one of them",
+            ));
+        }
+        if self.members.is_empty() {
+            diagnostics.push(Diagnostic::warning(
+                "the crate has no members, so its Kotlin file is empty",
+            ));
+        }
+
+        let crate_module = self.name.replace('-', "_");
+        let mut names = Vec::new();
+        for member in &self.members {
+            let error = |message: String| Diagnostic::error(message).in_member(member.name());
+            let Member::Function(function) = member else {
+                diagnostics.push(error(format!(
+                    "`{}` members are not supported yet; only functions are bound",
+                    member.kind()
+                )));
+                continue;
+            };
+
+            let path = function.name.split("::").collect::<Vec<_>>();
+            if !path.iter().all(|s| is_rust_identifier(s)) {
+                diagnostics.push(error(
+                    "the name is not a path of Rust identifiers, such as `my_crate::module::function`"
+                        .to_string(),
+                ));
+            } else if path.len() < 2 || path[0] != crate_module {
+                diagnostics.push(error(format!(
+                    "the name must be the full path of the function, starting with `{}::`",
+                    crate_module
+                )));
+            }
+            if names.contains(&&function.name) {
+                diagnostics.push(error("the member is given more than once".to_string()));
+            }
+            names.push(&function.name);
+
+            let mut input_names = Vec::new();
+            for input in &function.inputs {
+                if !is_rust_identifier(&input.name) {
+                    diagnostics.push(
+                        error("the name is not a Rust identifier".to_string())
+                            .in_input(&input.name),
+                    );
+                }
+                if input_names.contains(&&input.name) {
+                    diagnostics.push(
+                        error("the name is used by more than one input".to_string())
+                            .in_input(&input.name),
+                    );
+                }
+                input_names.push(&input.name);
+            }
+
+            let is_generic = function.generics.as_ref().is_some_and(|g| !g.is_empty());
+            if is_generic
+                && function
+                    .instantiations
+                    .as_ref()
+                    .is_none_or(|i| i.is_empty())
+            {
+                diagnostics.push(
+                    Diagnostic::warning(
+                        "the function is generic but has no instantiations, so it is not bound",
+                    )
+                    .in_member(&function.name),
+                );
+            }
+        }
+        diagnostics
+    }
+}
+
+/// The version of the config format, given by the `version` field of a config.
+pub const CONFIG_VERSION: u64 = 2;
+
+/// A config of the crates to bind with jni-binding-generator.
+#[derive(Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+struct Config {
+    /// The JSON Schema of the config, which editors use to check it.
+    #[serde(rename = "$schema")]
+    #[allow(dead_code)]
+    schema: Option<String>,
+    /// The version of the config format.
+    #[schemars(extend("const" = CONFIG_VERSION))]
+    #[allow(dead_code)]
+    version: u64,
+    // The crates are parsed separately so that all of their errors are found
+    #[schemars(with = "Vec<Crate>")]
+    crates: Vec<Value>,
+}
+
+/// The JSON Schema of the current config format.
+pub fn schema() -> Schema {
+    schema_for!(Config)
+}
+
+/// Parses a config, returning the errors of every crate, member and input that cannot be read.
+/// Configs in an older format are migrated first.
+pub fn parse(json: &str) -> Result<Vec<Crate>, Vec<Diagnostic>> {
+    let value: Value = from_str(json).map_err(|e| vec![get_json_diagnostic(&e)])?;
+    parse_value(migrate(value).map_err(|e| vec![Diagnostic::error(e)])?)
+}
+
+/// Rewrites a config in an older format in the current one, keeping the order of its fields.
+pub fn upgrade(json: &str) -> Result<String, Vec<Diagnostic>> {
+    let value: Value = from_str(json).map_err(|e| vec![get_json_diagnostic(&e)])?;
+    let value = migrate(value).map_err(|e| vec![Diagnostic::error(e)])?;
+    parse_value(value.clone())?;
+    Ok(format!(
+        "{}\n",
+        serde_json::to_string_pretty(&value).unwrap()
+    ))
+}
+
+/// Migrates a config to the current version of the format. The first version was an array of
+/// crates without a version.
+fn migrate(value: Value) -> Result<Value, String> {
+    let mut value = match value {
+        Value::Array(crates) => json!({ "version": 1, "crates": crates }),
+        value => value,
+    };
+    let version = value["version"].as_u64().ok_or_else(|| {
+        format!(
+            "the config has no `version`; the current version is {}",
+            CONFIG_VERSION
+        )
+    })?;
+    if version == 0 || version > CONFIG_VERSION {
+        return Err(format!(
+            "the config has version {}, but only versions 1 to {} are supported",
+            version, CONFIG_VERSION
+        ));
+    }
+
+    if version < 2 {
+        migrate_v1(&mut value);
+    }
+    value["version"] = json!(CONFIG_VERSION);
+    Ok(value)
+}
+
+/// Renames the `rust_type` of inputs to `type`, as in the first README, and removes the `null`
+/// fields of members and inputs, since `inputs` cannot be `null` anymore.
+fn migrate_v1(value: &mut Value) {
+    let Some(crates) = value.get_mut("crates").and_then(Value::as_array_mut) else {
+        return;
+    };
+    for package in crates {
+        let Some(members) = package.get_mut("members").and_then(Value::as_array_mut) else {
+            continue;
+        };
+        for member in members.iter_mut().filter_map(Value::as_object_mut) {
+            member.retain(|_, value| !value.is_null());
+            let Some(inputs) = member.get_mut("inputs").and_then(Value::as_array_mut) else {
+                continue;
+            };
+            for input in inputs.iter_mut().filter_map(Value::as_object_mut) {
+                input.retain(|_, value| !value.is_null());
+                if !input.contains_key("type")
+                    && let Some(rust_type) = input.shift_remove("rust_type")
+                {
+                    input.insert("type".to_string(), rust_type);
+                }
+            }
+        }
+    }
+}
+
+/// Parses a migrated config. A `Value` has no positions, so its errors are located by the crate,
+/// member and input they are in.
+fn parse_value(value: Value) -> Result<Vec<Crate>, Vec<Diagnostic>> {
+    let config = Config::deserialize(value).map_err(|e| vec![Diagnostic::error(e.to_string())])?;
+
+    let mut crates = Vec::new();
+    let mut errors = Vec::new();
+    for value in config.crates {
+        match parse_crate(value) {
+            Ok(package) => crates.push(package),
+            Err(e) => errors.extend(e),
+        }
+    }
+    if errors.is_empty() {
+        Ok(crates)
+    } else {
+        Err(errors)
+    }
+}
+
+/// A diagnostic at the position of an error in the JSON, such as a syntax error.
+fn get_json_diagnostic(error: &serde_json::Error) -> Diagnostic {
+    let message = error.to_string();
+    // The position is part of the message too
+    let message = match message.rsplit_once(" at line ") {
+        Some((message, _)) => message.to_string(),
+        None => message,
+    };
+    Diagnostic::error(message).at(error.line(), error.column())
+}
+
+/// Parses a crate, and each of its members separately so that all of their errors are found.
+fn parse_crate(mut value: Value) -> Result<Crate, Vec<Diagnostic>> {
+    let name = value["name"].as_str().map(str::to_string);
+    let in_crate = |diagnostic: Diagnostic| match &name {
+        Some(name) => diagnostic.in_crate(name),
+        None => diagnostic,
+    };
+
+    let members = value
+        .get_mut("members")
+        .map(|members| std::mem::replace(members, Value::Array(Vec::new())));
+    let mut package =
+        Crate::deserialize(value).map_err(|e| vec![in_crate(Diagnostic::error(e.to_string()))])?;
+
+    let members = Vec::<Value>::deserialize(members.unwrap_or_default())
+        .map_err(|e| vec![in_crate(Diagnostic::error(format!("members: {}", e)))])?;
+    let mut errors = Vec::new();
+    for member in members {
+        match parse_member(member) {
+            Ok(member) => package.members.push(member),
+            Err(e) => errors.extend(e.into_iter().map(in_crate)),
+        }
+    }
+    if errors.is_empty() {
+        Ok(package)
+    } else {
+        Err(errors)
+    }
+}
+
+fn parse_member(mut value: Value) -> Result<Member, Vec<Diagnostic>> {
+    let name = value["name"].as_str().map(str::to_string);
+    let in_member = |diagnostic: Diagnostic| match &name {
+        Some(name) => diagnostic.in_member(name),
+        None => diagnostic,
+    };
+
+    // Other members have no inputs, which is reported as an unknown field
+    let inputs = match value.as_object_mut() {
+        Some(member)
+            if member.get("type").is_some_and(|t| t == "function")
+                && member.get("inputs").is_some_and(Value::is_array) =>
+        {
+            member.remove("inputs")
+        }
+        _ => None,
+    };
+    let mut errors = Vec::new();
+    let mut member = Member::deserialize(value)
+        .map_err(|e| errors.push(in_member(Diagnostic::error(e.to_string()))))
+        .ok();
+
+    if let Some(Value::Array(inputs)) = inputs {
+        let mut parsed_inputs = Vec::new();
+        for input in inputs {
+            let input_name = input["name"].as_str().map(str::to_string);
+            match Input::deserialize(input) {
+                Ok(input) => parsed_inputs.push(input),
+                Err(e) => {
+                    let diagnostic = in_member(Diagnostic::error(e.to_string()));
+                    errors.push(match &input_name {
+                        Some(name) => diagnostic.in_input(name),
+                        None => diagnostic,
+                    });
+                }
+            }
+        }
+        if let Some(Member::Function(function)) = &mut member {
+            function.inputs = parsed_inputs;
+        }
+    }
+
+    match member {
+        Some(member) if errors.is_empty() => Ok(member),
+        _ => Err(errors),
+    }
+}
+
+/// A config binding a single function of `crate_name`, to start a config from.
+pub fn starter_config(crate_name: &str) -> String {
+    let config = json!({
+        "version": CONFIG_VERSION,
+        "crates": [
+            {
+                "name": crate_name,
+                "members": [
+                    {
+                        "type": "function",
+                        "name": format!("{}::add", crate_name.replace('-', "_")),
+                        "inputs": [
+                            { "name": "a", "type": "i32" },
+                            { "name": "b", "type": "i32" }
+                        ],
+                        "output": "i32"
+                    }
+                ]
+            }
+        ]
+    });
+    format!("{}\n", serde_json::to_string_pretty(&config).unwrap())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::error::Location;
+    use rstest::rstest;
+
+    fn first_function(config: &[Crate]) -> &Function {
+        match &config[0].members[0] {
+            Member::Function(function) => function,
+            _ => panic!("Expected a function"),
+        }
+    }
 
     #[test]
     fn test_parse_empty_array() {
@@ -57,8 +573,8 @@ mod tests {
         assert_eq!(result.len(), 1);
         assert_eq!(result[0].name, "test_crate");
         assert_eq!(result[0].members.len(), 1);
-        assert_eq!(result[0].members[0].member_type, "function");
-        assert_eq!(result[0].members[0].name, "test_fn");
+        assert_eq!(result[0].members[0].kind(), "function");
+        assert_eq!(result[0].members[0].name(), "test_fn");
     }
 
     #[test]
@@ -82,16 +598,502 @@ mod tests {
             }
         ]"#;
         let result = parse(json).unwrap();
-        let member = &result[0].members[0];
-        assert_eq!(member.inputs.as_ref().unwrap().len(), 1);
-        assert_eq!(member.inputs.as_ref().unwrap()[0].rust_type, "String");
-        assert_eq!(member.inputs.as_ref().unwrap()[0].name, "input1");
-        assert_eq!(member.output.as_ref().unwrap(), "bool");
+        let member = first_function(&result);
+        assert_eq!(member.inputs.len(), 1);
+        assert_eq!(
+            member.inputs[0].rust_type,
+            RustType::Named("String".to_string())
+        );
+        assert_eq!(member.inputs[0].name, "input1");
+        assert_eq!(
+            member.output.as_ref().unwrap(),
+            &RustType::Named("bool".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_normalizes_types() {
+        let json = r#"[
+            {
+                "name": "test_crate",
+                "members": [
+                    {
+                        "type": "function",
+                        "name": "test_fn",
+                        "inputs": [
+                            {
+                                "type": "& 'a str",
+                                "name": "input1"
+                            }
+                        ],
+                        "output": "std::vec::Vec<f32>"
+                    }
+                ]
+            }
+        ]"#;
+        let result = parse(json).unwrap();
+        let member = first_function(&result);
+        assert_eq!(member.inputs[0].rust_type.to_string(), "&str");
+        assert_eq!(member.output.as_ref().unwrap().to_string(), "Vec<f32>");
+    }
+
+    #[test]
+    fn test_parse_unsupported_type() {
+        let json = r#"[
+            {
+                "name": "test_crate",
+                "members": [
+                    {
+                        "type": "function",
+                        "name": "test_fn",
+                        "inputs": null,
+                        "output": "(f32, f32)"
+                    }
+                ]
+            }
+        ]"#;
+        let errors = parse(json).err().unwrap();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].location.crate_name.as_deref(), Some("test_crate"));
+        assert_eq!(errors[0].location.member.as_deref(), Some("test_fn"));
+        assert!(
+            errors[0]
+                .message
+                .starts_with("Unsupported type `(f32, f32)`: tuples are not supported")
+        );
+    }
+
+    #[test]
+    fn test_parse_collects_errors() {
+        let json = r#"[
+            {
+                "name": "test_crate",
+                "members": [
+                    {
+                        "type": "function",
+                        "name": "first",
+                        "inputs": [
+                            {"name": "valid", "type": "i32"},
+                            {"name": "tuple", "type": "(i32, i32)"},
+                            {"name": "array", "type": "[i32; 2]"}
+                        ]
+                    },
+                    {"type": "function", "name": "second", "output": "*const u8"},
+                    {"type": "function", "name": "valid", "output": "i32"}
+                ]
+            },
+            {"name": "no_members"}
+        ]"#;
+        let errors = parse(json).err().unwrap();
+        let locations = errors
+            .iter()
+            .map(|e| {
+                (
+                    e.location.crate_name.as_deref(),
+                    e.location.member.as_deref(),
+                    e.location.input.as_deref(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            locations,
+            [
+                (Some("test_crate"), Some("first"), Some("tuple")),
+                (Some("test_crate"), Some("first"), Some("array")),
+                (Some("test_crate"), Some("second"), None),
+                (Some("no_members"), None, None),
+            ]
+        );
+        assert_eq!(errors[3].message, "missing field `members`");
     }
 
     #[test]
     fn test_parse_invalid_json() {
-        let json = "invalid json";
-        assert!(parse(json).is_err());
+        let json = "[\n  {\"name\": \"test_crate\",}\n]";
+        let errors = parse(json).err().unwrap();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].location.position, Some((2, 25)));
+        assert_eq!(errors[0].message, "trailing comma");
+    }
+
+    #[rstest]
+    #[case::current(concat!(
+        "{\"version\": 2, \"crates\": [\n",
+        "  {\"name\": \"test_crate\", \"members\": [\n",
+        "    {\"type\": \"function\", \"name\": \"first\", \"inputs\": [{\"name\": \"a\", \"rust_type\": \"i32\"}]},\n",
+        "    {\"type\": \"function\", \"name\": \"second\", \"output\": \"(i32, i32)\"}\n",
+        "  ]},\n",
+        "  {\"name\": \"other\", \"typo\": true, \"members\": []}\n",
+        "]}",
+    ))]
+    #[case::first_format(concat!(
+        "[\n",
+        "  {\"name\": \"test_crate\", \"members\": [\n",
+        "    {\"type\": \"function\", \"name\": \"first\", \"inputs\": [{\"name\": \"a\", \"kind\": \"i32\"}]},\n",
+        "    {\"type\": \"function\", \"name\": \"second\", \"output\": \"(i32, i32)\"}\n",
+        "  ]},\n",
+        "  {\"name\": \"other\", \"typo\": true, \"members\": []}\n",
+        "]",
+    ))]
+    fn test_parse_error_locations(#[case] json: &str) {
+        let errors = parse(json).err().unwrap();
+        let locations = errors.into_iter().map(|e| e.location).collect::<Vec<_>>();
+        let location = |crate_name: &str, member: Option<&str>, input: Option<&str>| Location {
+            crate_name: Some(crate_name.to_string()),
+            member: member.map(str::to_string),
+            input: input.map(str::to_string),
+            ..Location::default()
+        };
+        assert_eq!(
+            locations,
+            [
+                location("test_crate", Some("first"), Some("a")),
+                location("test_crate", Some("second"), None),
+                location("other", None, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_monomorphize() {
+        let json = r#"[
+            {
+                "name": "test_crate",
+                "members": [
+                    {
+                        "type": "function",
+                        "name": "test_crate::mean",
+                        "generics": ["T"],
+                        "instantiations": [{ "T": "f32" }, { "T": "f64" }],
+                        "inputs": [
+                            {
+                                "type": "&[T]",
+                                "name": "values"
+                            }
+                        ],
+                        "output": "T"
+                    }
+                ]
+            }
+        ]"#;
+        let result = parse(json).unwrap();
+        let members = first_function(&result).monomorphize().unwrap();
+        assert_eq!(members.len(), 2);
+        assert_eq!(members[0].inputs[0].rust_type.to_string(), "&[f32]");
+        assert_eq!(members[0].output.as_ref().unwrap().to_string(), "f32");
+        assert_eq!(
+            members[0].type_arguments().unwrap(),
+            vec![&RustType::Named("f32".to_string())]
+        );
+        assert_eq!(members[1].inputs[0].rust_type.to_string(), "&[f64]");
+        assert_eq!(members[1].output.as_ref().unwrap().to_string(), "f64");
+    }
+
+    #[test]
+    fn test_monomorphize_non_generic() {
+        let json = r#"[
+            {
+                "name": "test_crate",
+                "members": [
+                    {
+                        "type": "function",
+                        "name": "test_fn",
+                        "inputs": null,
+                        "output": "f32"
+                    }
+                ]
+            }
+        ]"#;
+        let result = parse(json).unwrap();
+        let members = first_function(&result).monomorphize().unwrap();
+        assert_eq!(members.len(), 1);
+        assert!(members[0].type_arguments().is_none());
+    }
+
+    #[test]
+    fn test_monomorphize_missing_binding() {
+        let json = r#"[
+            {
+                "name": "test_crate",
+                "members": [
+                    {
+                        "type": "function",
+                        "name": "test_crate::convert",
+                        "generics": ["T", "U"],
+                        "instantiations": [{ "T": "f32" }],
+                        "inputs": [
+                            {
+                                "type": "T",
+                                "name": "value"
+                            }
+                        ],
+                        "output": "U"
+                    }
+                ]
+            }
+        ]"#;
+        let result = parse(json).unwrap();
+        let error = first_function(&result).monomorphize().err().unwrap();
+        assert_eq!(
+            error.to_string(),
+            "Unsupported type `U`: no concrete type is given for it in an instantiation of `test_crate::convert`"
+        );
+    }
+
+    #[test]
+    fn test_monomorphize_invalid_substitution() {
+        let json = r#"[
+            {
+                "name": "test_crate",
+                "members": [
+                    {
+                        "type": "function",
+                        "name": "test_crate::first",
+                        "generics": ["T"],
+                        "instantiations": [{ "T": "&str" }],
+                        "inputs": [
+                            {
+                                "type": "Vec<T>",
+                                "name": "values"
+                            }
+                        ]
+                    }
+                ]
+            }
+        ]"#;
+        let result = parse(json).unwrap();
+        assert!(first_function(&result).monomorphize().is_err());
+    }
+
+    #[test]
+    fn test_parse_naming() {
+        let json = r#"[
+            {
+                "name": "test_crate",
+                "naming": {
+                    "acronyms": ["GPS", "NMEA"],
+                    "strip_prefixes": ["ffi_"]
+                },
+                "members": [
+                    {
+                        "type": "function",
+                        "name": "test_crate::gps::ffi_parse_gps_nmea",
+                        "kotlin_name": "parseNmea",
+                        "kotlin_object": "Location.Gps",
+                        "inputs": [
+                            {
+                                "type": "&str",
+                                "name": "raw_sentence",
+                                "kotlin_name": "sentence"
+                            }
+                        ]
+                    }
+                ]
+            }
+        ]"#;
+        let result = parse(json).unwrap();
+        let naming = result[0].naming.as_ref().unwrap();
+        assert_eq!(naming.to_pascal_case("gps"), "GPS");
+        assert_eq!(
+            naming.to_camel_case(naming.strip_prefix("ffi_parse_gps_nmea")),
+            "parseGPSNMEA"
+        );
+        let member = first_function(&result);
+        assert_eq!(member.kotlin_name.as_deref(), Some("parseNmea"));
+        assert_eq!(member.kotlin_object.as_deref(), Some("Location.Gps"));
+        assert_eq!(member.inputs[0].kotlin_name.as_deref(), Some("sentence"));
+    }
+
+    #[rstest]
+    #[case("ffi_parse", "parse")]
+    #[case("parse", "parse")]
+    #[case("ffi_", "ffi_")]
+    fn test_strip_prefix(#[case] input: &str, #[case] expected: &str) {
+        let naming = Naming {
+            acronyms: None,
+            strip_prefixes: Some(vec!["ffi_".to_string()]),
+            renames: None,
+        };
+        assert_eq!(naming.strip_prefix(input), expected);
+    }
+
+    #[test]
+    fn test_starter_config() {
+        let config = parse(&starter_config("my-crate")).unwrap();
+        assert_eq!(config.len(), 1);
+        assert_eq!(config[0].name, "my-crate");
+        assert_eq!(config[0].members[0].name(), "my_crate::add");
+        assert_eq!(first_function(&config).inputs.len(), 2);
+    }
+
+    #[rstest]
+    #[case::valid(r#"{"type": "function", "name": "my_crate::geo::distance", "inputs": [{"name": "a", "type": "f32"}]}"#, vec![])]
+    #[case::unsupported_member(
+        r#"{"type": "struct", "name": "my_crate::Point"}"#,
+        vec![Diagnostic::error("`struct` members are not supported yet; only functions are bound").in_member("my_crate::Point")]
+    )]
+    #[case::invalid_path(
+        r#"{"type": "function", "name": "my_crate::parse-nmea"}"#,
+        vec![Diagnostic::error("the name is not a path of Rust identifiers, such as `my_crate::module::function`").in_member("my_crate::parse-nmea")]
+    )]
+    #[case::other_crate(
+        r#"{"type": "function", "name": "other::parse"}"#,
+        vec![Diagnostic::error("the name must be the full path of the function, starting with `my_crate::`").in_member("other::parse")]
+    )]
+    #[case::unqualified(
+        r#"{"type": "function", "name": "parse"}"#,
+        vec![Diagnostic::error("the name must be the full path of the function, starting with `my_crate::`").in_member("parse")]
+    )]
+    #[case::invalid_inputs(
+        r#"{"type": "function", "name": "my_crate::f", "inputs": [{"name": "type", "type": "f32"}, {"name": "a", "type": "f32"}, {"name": "a", "type": "f64"}]}"#,
+        vec![
+            Diagnostic::error("the name is not a Rust identifier").in_member("my_crate::f").in_input("type"),
+            Diagnostic::error("the name is used by more than one input").in_member("my_crate::f").in_input("a"),
+        ]
+    )]
+    #[case::no_instantiations(
+        r#"{"type": "function", "name": "my_crate::mean", "generics": ["T"], "inputs": [{"name": "v", "type": "&[T]"}]}"#,
+        vec![Diagnostic::warning("the function is generic but has no instantiations, so it is not bound").in_member("my_crate::mean")]
+    )]
+    fn test_validate_crate(#[case] member: &str, #[case] expected: Vec<Diagnostic>) {
+        let json = format!(r#"[{{"name": "my-crate", "members": [{}]}}]"#, member);
+        assert_eq!(parse(&json).unwrap()[0].validate(), expected);
+    }
+
+    #[test]
+    fn test_validate_crate_duplicates() {
+        let json = r#"[{
+            "name": "my-crate",
+            "path": "../my-crate",
+            "git": "https://example.com/my-crate.git",
+            "members": [
+                {"type": "function", "name": "my_crate::f"},
+                {"type": "function", "name": "my_crate::f"}
+            ]
+        }]"#;
+        assert_eq!(
+            parse(json).unwrap()[0].validate(),
+            vec![
+                Diagnostic::error(
+                    "both a `path` and a `git` repository are given; give one of them"
+                ),
+                Diagnostic::error("the member is given more than once").in_member("my_crate::f"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_crate_without_members() {
+        let json = r#"[{"name": "my-crate", "members": []}]"#;
+        assert_eq!(
+            parse(json).unwrap()[0].validate(),
+            vec![Diagnostic::warning(
+                "the crate has no members, so its Kotlin file is empty"
+            )]
+        );
+    }
+
+    #[rstest]
+    #[case::unknown_field(
+        r#"{"version": 2, "crates": [{"name": "my-crate", "members": [{"type": "function", "name": "my_crate::f", "inputs": [{"name": "a", "rust_type": "i32"}]}]}]}"#,
+        "unknown field `rust_type`, expected one of `type`, `name`, `kotlin_name`, `docs`, `default`"
+    )]
+    #[case::unknown_member_type(
+        r#"{"version": 2, "crates": [{"name": "my-crate", "members": [{"type": "fn", "name": "my_crate::f"}]}]}"#,
+        "unknown variant `fn`, expected one of `function`, `struct`, `enum`, `const`"
+    )]
+    #[case::inputs_of_struct(
+        r#"{"version": 2, "crates": [{"name": "my-crate", "members": [{"type": "struct", "name": "my_crate::Point", "inputs": []}]}]}"#,
+        "unknown field `inputs`, expected `name`"
+    )]
+    #[case::newer_version(
+        r#"{"version": 3, "crates": []}"#,
+        "the config has version 3, but only versions 1 to 2 are supported"
+    )]
+    #[case::no_version(
+        r#"{"crates": []}"#,
+        "the config has no `version`; the current version is 2"
+    )]
+    fn test_parse_rejects(#[case] json: &str, #[case] expected: &str) {
+        let errors = parse(json).err().unwrap();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].message, expected);
+    }
+
+    #[test]
+    fn test_upgrade() {
+        let json = r#"[
+            {
+                "name": "my-crate",
+                "members": [
+                    {"type": "function", "name": "my_crate::now", "inputs": null, "output": "i64"},
+                    {"type": "function", "name": "my_crate::abs", "inputs": [{"name": "x", "rust_type": "i32"}]}
+                ]
+            }
+        ]"#;
+        let upgraded = upgrade(json).unwrap();
+        assert_eq!(
+            upgraded,
+            r#"{
+  "version": 2,
+  "crates": [
+    {
+      "name": "my-crate",
+      "members": [
+        {
+          "type": "function",
+          "name": "my_crate::now",
+          "output": "i64"
+        },
+        {
+          "type": "function",
+          "name": "my_crate::abs",
+          "inputs": [
+            {
+              "name": "x",
+              "type": "i32"
+            }
+          ]
+        }
+      ]
+    }
+  ]
+}
+"#
+        );
+        assert_eq!(upgrade(&upgraded).unwrap(), upgraded);
+    }
+
+    #[test]
+    fn test_schema() {
+        let schema = schema();
+        let schema = schema.as_value();
+        assert_eq!(schema["properties"]["version"]["const"], CONFIG_VERSION);
+        assert_eq!(schema["additionalProperties"], false);
+        let member_types = schema["$defs"]["Member"]["oneOf"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|variant| variant["properties"]["type"]["const"].as_str().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(member_types, ["function", "struct", "enum", "const"]);
+        assert_eq!(schema["$defs"]["RustType"]["type"], "string");
+    }
+
+    /// The example config of the README is a valid config.
+    #[test]
+    fn test_readme_example() {
+        let readme = include_str!("../README.md");
+        let (_, example) = readme.split_once("A config looks like this").unwrap();
+        let (_, example) = example.split_once("```json\n").unwrap();
+        let (example, _) = example.split_once("```").unwrap();
+        let config = parse(example).unwrap();
+        assert!(config.iter().all(|package| package.validate().is_empty()));
+    }
+
+    #[test]
+    fn test_parse_schema_reference() {
+        let json = r#"{"$schema": "./config.schema.json", "version": 2, "crates": []}"#;
+        assert_eq!(parse(json).unwrap().len(), 0);
     }
 }
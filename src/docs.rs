@@ -0,0 +1,456 @@
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use syn::{Attribute, Expr, Item, Lit, Meta, Visibility};
+
+/// Collects the doc comments of the public functions of a crate, keyed by their fully qualified
+/// name such as `my_crate::geo::distance`. Modules are followed from the library root the same
+/// way rustc finds them, so modules with a `#[path]` attribute are skipped.
+pub fn find_function_docs(
+    crate_name: &str,
+    lib_root: &Path,
+) -> io::Result<HashMap<String, String>> {
+    let mut docs = HashMap::new();
+    let directory = lib_root.parent().map(Path::to_path_buf);
+    collect_file_docs(lib_root, crate_name, directory, &mut docs)?;
+    Ok(docs)
+}
+
+fn collect_file_docs(
+    file: &Path,
+    module_path: &str,
+    directory: Option<PathBuf>,
+    docs: &mut HashMap<String, String>,
+) -> io::Result<()> {
+    let source = fs::read_to_string(file)?;
+    let parsed = syn::parse_file(&source).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{}: {}", file.display(), e),
+        )
+    })?;
+    collect_item_docs(&parsed.items, module_path, directory, docs)
+}
+
+/// Collects the docs of `items`, which are declared in `module_path`. `directory` is where the
+/// files of the modules declared in `items` are, if they can be found.
+fn collect_item_docs(
+    items: &[Item],
+    module_path: &str,
+    directory: Option<PathBuf>,
+    docs: &mut HashMap<String, String>,
+) -> io::Result<()> {
+    for item in items {
+        match item {
+            Item::Fn(function) if matches!(function.vis, Visibility::Public(_)) => {
+                if let Some(doc) = get_doc(&function.attrs) {
+                    docs.insert(format!("{}::{}", module_path, function.sig.ident), doc);
+                }
+            }
+            Item::Mod(module) if matches!(module.vis, Visibility::Public(_)) => {
+                let name = module.ident.to_string();
+                let child_path = format!("{}::{}", module_path, name);
+                let child_directory = directory.as_ref().map(|d| d.join(&name));
+                match &module.content {
+                    Some((_, items)) => {
+                        collect_item_docs(items, &child_path, child_directory, docs)?
+                    }
+                    None if module.attrs.iter().any(|a| a.path().is_ident("path")) => {}
+                    None => {
+                        let Some(directory) = directory.as_ref() else {
+                            continue;
+                        };
+                        let file = directory.join(format!("{}.rs", name));
+                        let mod_file = directory.join(&name).join("mod.rs");
+                        if file.exists() {
+                            collect_file_docs(&file, &child_path, child_directory, docs)?;
+                        } else if mod_file.exists() {
+                            collect_file_docs(&mod_file, &child_path, child_directory, docs)?;
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Joins the `///` comments of an item, without the space that follows the slashes.
+fn get_doc(attributes: &[Attribute]) -> Option<String> {
+    let lines = attributes
+        .iter()
+        .filter(|a| a.path().is_ident("doc"))
+        .filter_map(|a| match &a.meta {
+            Meta::NameValue(name_value) => match &name_value.value {
+                Expr::Lit(expr) => match &expr.lit {
+                    Lit::Str(doc) => Some(doc.value()),
+                    _ => None,
+                },
+                _ => None,
+            },
+            _ => None,
+        })
+        .flat_map(|doc| {
+            // `lines` would skip the empty string of an empty `///` line
+            doc.split('\n')
+                .map(|l| l.strip_prefix(' ').unwrap_or(l).to_string())
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    if lines.is_empty() {
+        None
+    } else {
+        Some(lines.join("\n"))
+    }
+}
+
+/// A parameter of a documented function.
+pub struct DocParameter<'a> {
+    /// The name of the Rust parameter, as used in the `# Arguments` section.
+    pub name: &'a str,
+    pub kotlin_name: String,
+    /// Documents the parameter in place of the `# Arguments` section.
+    pub docs: Option<&'a str>,
+}
+
+enum Section {
+    Description,
+    Arguments,
+    Returns,
+    Throws,
+}
+
+/// Translates a Rust doc comment to a KDoc comment. The `# Arguments` section becomes `@param`
+/// tags, named after the Kotlin names of the `parameters`, and the `# Returns`, `# Errors` and
+/// `# Panics` sections become `@return` and `@throws` tags.
+pub fn to_kdoc(doc: &str, parameters: &[DocParameter]) -> String {
+    let mut description: Vec<String> = Vec::new();
+    let mut params: Vec<(String, String)> = Vec::new();
+    let mut returns: Vec<String> = Vec::new();
+    let mut throws: Vec<Vec<String>> = Vec::new();
+    let mut section = Section::Description;
+    let mut code_block: Option<bool> = None;
+
+    for line in doc.lines() {
+        let trimmed = line.trim();
+
+        if let Some(language) = trimmed.strip_prefix("```") {
+            code_block = match code_block {
+                // Unlabeled code blocks are Rust in Rust docs
+                None => Some(
+                    language.is_empty()
+                        || language
+                            .split(',')
+                            .any(|l| matches!(l, "rust" | "ignore" | "no_run" | "should_panic")),
+                ),
+                Some(_) => None,
+            };
+            description.push(line.to_string());
+            continue;
+        }
+        if let Some(is_rust) = code_block {
+            // Lines starting with `#` are hidden from Rust doc examples
+            if !(is_rust && (trimmed == "#" || trimmed.starts_with("# "))) {
+                description.push(line.to_string());
+            }
+            continue;
+        }
+
+        if let Some(heading) = trimmed.strip_prefix("# ") {
+            section = match heading.trim().to_lowercase().as_str() {
+                "arguments" | "parameters" => Section::Arguments,
+                "returns" => Section::Returns,
+                "errors" | "panics" => {
+                    throws.push(Vec::new());
+                    Section::Throws
+                }
+                _ => {
+                    description.push(line.to_string());
+                    Section::Description
+                }
+            };
+            continue;
+        }
+
+        // Link reference definitions point to Rust items
+        if trimmed.starts_with('[') && trimmed.contains("]: ") {
+            continue;
+        }
+
+        let line = remove_intra_doc_links(line);
+        match section {
+            Section::Description => description.push(line),
+            Section::Arguments => match parse_argument(trimmed) {
+                Some((name, text)) => {
+                    let name = parameters
+                        .iter()
+                        .find(|p| p.name == name)
+                        .map(|p| p.kotlin_name.clone())
+                        .unwrap_or(name);
+                    params.push((name, remove_intra_doc_links(&text)));
+                }
+                None if !trimmed.is_empty() => {
+                    if let Some((_, text)) = params.last_mut() {
+                        text.push(' ');
+                        text.push_str(line.trim());
+                    }
+                }
+                None => {}
+            },
+            Section::Returns if !trimmed.is_empty() => returns.push(line.trim().to_string()),
+            Section::Throws if !trimmed.is_empty() => {
+                throws.last_mut().unwrap().push(line.trim().to_string())
+            }
+            _ => {}
+        }
+    }
+
+    while description.last().is_some_and(|l| l.trim().is_empty()) {
+        description.pop();
+    }
+    while description.first().is_some_and(|l| l.trim().is_empty()) {
+        description.remove(0);
+    }
+
+    for parameter in parameters {
+        let Some(docs) = parameter.docs else {
+            continue;
+        };
+        let docs = docs.lines().collect::<Vec<_>>().join(" ");
+        match params
+            .iter_mut()
+            .find(|(name, _)| *name == parameter.kotlin_name)
+        {
+            Some((_, text)) => *text = docs,
+            None => params.push((parameter.kotlin_name.clone(), docs)),
+        }
+    }
+
+    let mut tags = Vec::new();
+    for (name, text) in params {
+        tags.push(format!("@param {} {}", name, text));
+    }
+    if !returns.is_empty() {
+        tags.push(format!("@return {}", returns.join(" ")));
+    }
+    for lines in throws.into_iter().filter(|l| !l.is_empty()) {
+        tags.push(format!("@throws RuntimeException {}", lines.join(" ")));
+    }
+
+    let mut lines = description;
+    if !lines.is_empty() && !tags.is_empty() {
+        lines.push(String::new());
+    }
+    lines.extend(tags);
+
+    let mut kdoc = String::from("/**\n");
+    for line in lines {
+        // A `*/` in the text would end the comment early
+        let line = line.replace("*/", "*&#47;");
+        if line.is_empty() {
+            kdoc.push_str(" *\n");
+        } else {
+            kdoc.push_str(&format!(" * {}\n", line));
+        }
+    }
+    kdoc.push_str(" */");
+    kdoc
+}
+
+/// Parses a list item such as ``* `name` - description`` from an arguments section.
+fn parse_argument(line: &str) -> Option<(String, String)> {
+    let item = line
+        .strip_prefix("* ")
+        .or_else(|| line.strip_prefix("- "))?
+        .trim();
+    let (name, rest) = match item.strip_prefix('`') {
+        Some(quoted) => quoted.split_once('`')?,
+        None => item.split_once(|c: char| c.is_whitespace() || c == ':')?,
+    };
+    let text = rest
+        .trim_start()
+        .trim_start_matches([':', '-', '\u{2013}', '\u{2014}'])
+        .trim();
+    Some((name.to_string(), text.to_string()))
+}
+
+/// Replaces intra-doc links such as ``[`Point`]`` with their code span, since the Rust items they
+/// link to do not exist in Kotlin.
+fn remove_intra_doc_links(line: &str) -> String {
+    let mut result = String::new();
+    let mut rest = line;
+    while let Some(start) = rest.find("[`") {
+        let Some(length) = rest[start + 2..].find("`]") else {
+            break;
+        };
+        let end = start + 2 + length + 2;
+        if rest[end..].starts_with('(') || rest[end..].starts_with('[') {
+            result.push_str(&rest[..end]);
+        } else {
+            result.push_str(&rest[..start]);
+            result.push_str(&rest[start + 1..end - 1]);
+        }
+        rest = &rest[end..];
+    }
+    result.push_str(rest);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[test]
+    fn test_collect_item_docs() {
+        let file = syn::parse_file(
+            r#"
+            /// Adds two numbers.
+            pub fn add(a: f32, b: f32) -> f32 { a + b }
+
+            /// Not public.
+            fn private() {}
+
+            pub fn undocumented() {}
+
+            pub mod geo {
+                /// The distance between two points.
+                ///
+                /// Uses `f64`.
+                pub fn distance(a: f64) -> f64 { a }
+            }
+            "#,
+        )
+        .unwrap();
+        let mut docs = HashMap::new();
+        collect_item_docs(&file.items, "my_crate", None, &mut docs).unwrap();
+        assert_eq!(
+            docs,
+            HashMap::from([
+                ("my_crate::add".to_string(), "Adds two numbers.".to_string()),
+                (
+                    "my_crate::geo::distance".to_string(),
+                    "The distance between two points.\n\nUses `f64`.".to_string()
+                ),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_to_kdoc() {
+        let doc = r#"Parses an NMEA sentence, such as [`Sentence`].
+
+# Arguments
+
+* `sentence` - The sentence to parse, without the
+  trailing checksum.
+* `strict`: Whether to reject unknown talkers.
+
+# Returns
+
+The parsed fix.
+
+# Errors
+
+If the sentence is malformed.
+
+# Panics
+
+If `sentence` is empty.
+
+# Examples
+
+```
+# use my_crate::parse;
+let fix = parse("$GPGGA", true);
+```
+
+[`Sentence`]: crate::Sentence"#;
+        let parameters = [DocParameter {
+            name: "sentence",
+            kotlin_name: "text".to_string(),
+            docs: None,
+        }];
+        assert_eq!(
+            to_kdoc(doc, &parameters),
+            r#"/**
+ * Parses an NMEA sentence, such as `Sentence`.
+ *
+ * # Examples
+ *
+ * ```
+ * let fix = parse("$GPGGA", true);
+ * ```
+ *
+ * @param text The sentence to parse, without the trailing checksum.
+ * @param strict Whether to reject unknown talkers.
+ * @return The parsed fix.
+ * @throws RuntimeException If the sentence is malformed.
+ * @throws RuntimeException If `sentence` is empty.
+ */"#
+        );
+    }
+
+    #[rstest]
+    #[case("Adds two numbers.", "/**\n * Adds two numbers.\n */")]
+    #[case("Matches `*/` paths.", "/**\n * Matches `*&#47;` paths.\n */")]
+    #[case(
+        "```text\n# not hidden\n```",
+        "/**\n * ```text\n * # not hidden\n * ```\n */"
+    )]
+    fn test_to_kdoc_description(#[case] doc: &str, #[case] expected: &str) {
+        assert_eq!(to_kdoc(doc, &[]), expected);
+    }
+
+    #[rstest]
+    #[case("* `a` - The first number.", Some(("a", "The first number.")))]
+    #[case("- b: The second number.", Some(("b", "The second number.")))]
+    #[case("* c The third number.", Some(("c", "The third number.")))]
+    #[case("Not a list item.", None)]
+    fn test_parse_argument(#[case] line: &str, #[case] expected: Option<(&str, &str)>) {
+        assert_eq!(
+            parse_argument(line),
+            expected.map(|(n, t)| (n.to_string(), t.to_string()))
+        );
+    }
+
+    #[rstest]
+    #[case("See [`Point`].", "See `Point`.")]
+    #[case("See [`Point`](crate::Point).", "See [`Point`](crate::Point).")]
+    #[case("See [`a`] and [`b`]", "See `a` and `b`")]
+    fn test_remove_intra_doc_links(#[case] line: &str, #[case] expected: &str) {
+        assert_eq!(remove_intra_doc_links(line), expected);
+    }
+
+    #[test]
+    fn test_to_kdoc_parameter_docs() {
+        let parameters = [
+            DocParameter {
+                name: "a",
+                kotlin_name: "first".to_string(),
+                docs: Some("Replaces the docs of `a`."),
+            },
+            DocParameter {
+                name: "b",
+                kotlin_name: "b".to_string(),
+                docs: Some("Only in the config."),
+            },
+        ];
+        assert_eq!(
+            to_kdoc(
+                "Adds.\n\n# Arguments\n\n* `a` - The first number.",
+                &parameters
+            ),
+            r#"/**
+ * Adds.
+ *
+ * @param first Replaces the docs of `a`.
+ * @param b Only in the config.
+ */"#
+        );
+    }
+}
@@ -0,0 +1,175 @@
+use std::{fmt, io, path::PathBuf};
+
+use crate::{cargo::CargoError, template::TemplateFileError};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// Where a diagnostic is in the config. Problems found while reading the JSON have a line and
+/// column, and the others the crate, member and input they are about.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Location {
+    pub file: Option<PathBuf>,
+    pub position: Option<(usize, usize)>,
+    pub crate_name: Option<String>,
+    pub member: Option<String>,
+    pub input: Option<String>,
+}
+
+/// A problem found in the config.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub location: Location,
+    pub message: String,
+}
+
+impl Diagnostic {
+    pub fn error(message: impl Into<String>) -> Diagnostic {
+        Diagnostic {
+            severity: Severity::Error,
+            location: Location::default(),
+            message: message.into(),
+        }
+    }
+
+    pub fn warning(message: impl Into<String>) -> Diagnostic {
+        Diagnostic {
+            severity: Severity::Warning,
+            ..Diagnostic::error(message)
+        }
+    }
+
+    pub fn in_file(mut self, file: impl Into<PathBuf>) -> Diagnostic {
+        self.location.file = Some(file.into());
+        self
+    }
+
+    pub fn at(mut self, line: usize, column: usize) -> Diagnostic {
+        self.location.position = Some((line, column));
+        self
+    }
+
+    pub fn in_crate(mut self, name: &str) -> Diagnostic {
+        self.location.crate_name = Some(name.to_string());
+        self
+    }
+
+    pub fn in_member(mut self, name: &str) -> Diagnostic {
+        self.location.member = Some(name.to_string());
+        self
+    }
+
+    pub fn in_input(mut self, name: &str) -> Diagnostic {
+        self.location.input = Some(name.to_string());
+        self
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.severity {
+            Severity::Error => write!(f, "error: ")?,
+            Severity::Warning => write!(f, "warning: ")?,
+        }
+
+        let location = &self.location;
+        if let Some(file) = &location.file {
+            write!(f, "{}", file.display())?;
+            if let Some((line, column)) = location.position {
+                write!(f, ":{}:{}", line, column)?;
+            }
+            write!(f, ": ")?;
+        }
+        let context = [
+            ("crate", &location.crate_name),
+            ("member", &location.member),
+            ("input", &location.input),
+        ]
+        .iter()
+        .filter_map(|(kind, name)| Some(format!("{} `{}`", kind, name.as_ref()?)))
+        .collect::<Vec<_>>();
+        if !context.is_empty() {
+            write!(f, "{}: ", context.join(", "))?;
+        }
+
+        write!(f, "{}", self.message)
+    }
+}
+
+/// An error that stops the generator.
+#[derive(Debug)]
+pub enum Error {
+    /// The config has errors, each described by a diagnostic.
+    Config(Vec<Diagnostic>),
+    Template(TemplateFileError),
+    Cargo(CargoError),
+    Io(io::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Config(diagnostics) => write!(
+                f,
+                "the config has {} error{}",
+                diagnostics.len(),
+                if diagnostics.len() == 1 { "" } else { "s" }
+            ),
+            Error::Template(error) => write!(f, "{}", error),
+            Error::Cargo(error) => write!(f, "{}", error),
+            Error::Io(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<TemplateFileError> for Error {
+    fn from(error: TemplateFileError) -> Self {
+        Error::Template(error)
+    }
+}
+
+impl From<CargoError> for Error {
+    fn from(error: CargoError) -> Self {
+        Error::Cargo(error)
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        Error::Io(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case::message(Diagnostic::error("invalid"), "error: invalid")]
+    #[case::position(
+        Diagnostic::error("expected `,`").in_file("config.json").at(3, 17),
+        "error: config.json:3:17: expected `,`"
+    )]
+    #[case::input(
+        Diagnostic::error("invalid default value").in_file("config.json").in_crate("demo").in_member("demo::add").in_input("a"),
+        "error: config.json: crate `demo`, member `demo::add`, input `a`: invalid default value"
+    )]
+    #[case::warning(
+        Diagnostic::warning("a Java keyword").in_crate("demo"),
+        "warning: crate `demo`: a Java keyword"
+    )]
+    fn test_display_diagnostic(#[case] diagnostic: Diagnostic, #[case] expected: &str) {
+        assert_eq!(diagnostic.to_string(), expected);
+    }
+}
@@ -1,170 +1,693 @@
-use std::collections::HashMap;
-
 use crate::{
-    config::Member,
-    names::{get_modules, get_unqualified_name, to_camel_case, to_pascal_case},
-    types::{convert_java_type_to_rust, convert_rust_type_to_java, get_jni_type, get_kotlin_type},
+    config::{Function, Input, Naming},
+    docs::{DocParameter, to_kdoc},
+    error::Diagnostic,
+    names::{
+        check_jvm_name, escape_jni_name, escape_kotlin_name, get_modules, get_unqualified_name,
+        is_java_keyword, strip_raw_prefix,
+    },
+    rust_type::RustType,
+    template::Templates,
+    types::{
+        check_type, convert_java_type_to_rust, convert_rust_type_to_java, get_input_setup,
+        get_input_write_back, get_jni_default_value, get_jni_input_signature, get_jni_input_type,
+        get_jni_type, get_kotlin_default_value, get_kotlin_input_type, get_kotlin_type,
+        get_wide_char_check,
+    },
 };
 
-const JNI_FUNCTION_TEMPLATE: &str = r#"#[unsafe(no_mangle)]
+pub const JNI_FUNCTION_TEMPLATE: &str = r#"{docs}#[unsafe(no_mangle)]
 pub extern "C" fn {name}(
-    mut env: JNIEnv,
-    _: JClass,
-{params}
+    {params}
 ){ret_type} {
-    {result_assignment}{func_name}({args});
-    {return}
+    {setup}{result_assignment}{call};{write_back}{return}
 }"#;
 
-const KOTLIN_FUNCTION_TEMPLATE: &str = r#"external fun {name}({params}){ret_type}"#;
+// Panics cannot unwind into the JVM, so the panics of fallible functions are thrown as exceptions
+pub const JNI_FALLIBLE_CALL_TEMPLATE: &str = r#"match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {func_name}({args}))) {
+    Ok(result) => result,
+    Err(panic) => {
+        let message = panic
+            .downcast_ref::<&str>()
+            .map(|m| m.to_string())
+            .or_else(|| panic.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "{func_name} panicked".to_string());
+        env.throw_new("java/lang/RuntimeException", message).unwrap();
+        return{default};
+    }
+}"#;
 
-fn populate_template(template: &str, parameters: &HashMap<String, String>) -> String {
-    let mut result = template.to_string();
-    for (key, value) in parameters {
-        result = result.replace(&format!("{{{}}}", key), value);
+// The `Ok` value is returned to Java, and the `Err` value is thrown with its `Display` message
+pub const JNI_RESULT_CALL_TEMPLATE: &str = r#"match {call} {
+    Ok(value) => value,
+    Err(error) => {
+        env.throw_new("java/lang/RuntimeException", error.to_string()).unwrap();
+        return{default};
     }
-    result
+}"#;
+
+pub const KOTLIN_FUNCTION_TEMPLATE: &str = r#"{docs}external fun {name}({params}){ret_type}"#;
+
+pub const KOTLIN_WRAPPER_TEMPLATE: &str = r#"{docs}fun {name}({params}){ret_type} = {external_name}({args})
+
+private external fun {external_name}({external_params}){ret_type}"#;
+
+pub const KOTLIN_CATCHING_TEMPLATE: &str = r#"/** Calls [{name}], returning the exception it throws as a failed [Result]. */
+fun {catching_name}({params}): Result<{result_type}> = runCatching { {name}({args}) }"#;
+
+/// Where a function is declared on the Kotlin side.
+#[derive(Debug, PartialEq)]
+pub struct KotlinPath {
+    /// The root object, named after the crate.
+    pub root: String,
+    /// The objects nested in the root object that contain the function, outermost first.
+    pub objects: Vec<String>,
+    pub name: String,
 }
 
-fn get_jni_function_name(java_package: &str, name: &str) -> String {
-    let module_names = get_modules(name)
-        .iter()
-        .map(|m| to_pascal_case(m))
-        .collect::<Vec<_>>()
-        .join("_");
-    let function_name = to_camel_case(&get_unqualified_name(name));
-    format!(
+impl KotlinPath {
+    /// The dot separated path relative to the root object, as written in Kotlin.
+    fn to_kotlin_string(&self) -> String {
+        let mut components = self.objects.clone();
+        components.push(self.name.clone());
+        components.join(".")
+    }
+}
+
+pub fn get_kotlin_path(function: &Function, naming: &Naming) -> KotlinPath {
+    let modules = get_modules(&function.name);
+    let root = modules
+        .first()
+        .map(|m| naming.to_pascal_case(m))
+        .unwrap_or_default();
+    let objects = match &function.kotlin_object {
+        Some(object) => object
+            .split('.')
+            .filter(|o| !o.is_empty())
+            .map(|o| naming.rename(o))
+            .collect(),
+        None => modules
+            .iter()
+            .skip(1)
+            .map(|m| naming.rename(&naming.to_pascal_case(m)))
+            .collect(),
+    };
+    let name = match &function.kotlin_name {
+        Some(name) => naming.rename(name),
+        None => naming.rename(
+            &naming.to_camel_case(naming.strip_prefix(&get_unqualified_name(&function.name))),
+        ),
+    };
+    KotlinPath {
+        root,
+        objects,
+        name,
+    }
+}
+
+/// The name of the private external function that a Kotlin wrapper calls.
+fn get_external_name(name: &str) -> String {
+    format!("{}Native", name)
+}
+
+/// Gets the JNI symbol of a function. Overloaded functions pass the descriptors of their inputs
+/// to use the long form of the symbol, which the JVM uses to tell overloads apart.
+fn get_jni_function_name(
+    java_package: &str,
+    path: &KotlinPath,
+    input_signature: Option<&str>,
+) -> String {
+    // Nested objects are nested classes, which are separated by `$` in the class name
+    let mut classes = vec![path.root.clone()];
+    classes.extend(path.objects.iter().cloned());
+    let short_name = format!(
         "Java_{}_{}_{}",
-        java_package.replace('_', "_1").replace('.', "_"),
-        module_names,
-        function_name
-    )
+        escape_jni_name(java_package),
+        escape_jni_name(&classes.join("$")),
+        escape_jni_name(&path.name)
+    );
+    match input_signature {
+        Some(signature) => format!("{}__{}", short_name, escape_jni_name(signature)),
+        None => short_name,
+    }
+}
+
+/// Whether a function is an overload on the Kotlin side, which must use the long form of its JNI
+/// symbol. Each instantiation of a generic function is an overload.
+fn is_overload(function: &Function) -> bool {
+    function.overload == Some(true) || function.type_arguments().is_some()
+}
+
+/// The concatenated type descriptors of the inputs of a function.
+fn get_input_signature(function: &Function) -> Result<String, String> {
+    function
+        .inputs
+        .iter()
+        .map(|i| get_jni_input_signature(&i.rust_type))
+        .collect()
 }
 
-pub fn generate_jni_function(java_package: &str, function: &Member) -> String {
-    let mut inputs = Vec::new();
+/// Generates the Rust wrapper of a function. With `wrappers`, the wrapper implements the private
+/// external function that the Kotlin wrapper calls. Returns the reason when a type of the function
+/// cannot be converted.
+pub fn generate_jni_function(
+    java_package: &str,
+    function: &Function,
+    naming: &Naming,
+    wrappers: bool,
+    templates: &Templates,
+) -> Result<String, String> {
+    let mut inputs = vec!["mut env: JNIEnv".to_string(), "_: JClass".to_string()];
 
-    for arg in function.inputs.as_ref().unwrap_or(&vec![]) {
+    for arg in &function.inputs {
         inputs.push(format!(
-            "    {}: {}",
+            "{}: {}",
             arg.name,
-            get_jni_type(&arg.rust_type)
+            get_jni_input_type(&arg.rust_type)?
         ));
     }
 
-    let output = function.output.as_ref().map(|o| get_jni_type(o));
+    let returned_type = function.returned_type();
+    let output = returned_type.map(get_jni_type).transpose()?;
 
-    let unqualified_name = function.name.split("::").last().unwrap();
+    // Each statement is on its own line, so the setup is followed by line breaks and the write
+    // back after the call is preceded by them
+    type GetStatement = fn(&str, &RustType) -> Result<Option<String>, String>;
+    let statements = |get: GetStatement, line: fn(String) -> String| {
+        let mut statements = String::new();
+        for input in &function.inputs {
+            if let Some(statement) = get(&input.name, &input.rust_type)? {
+                statements.push_str(&line(statement));
+            }
+        }
+        Ok::<_, String>(statements)
+    };
+
+    let type_arguments = function.type_arguments();
+    let input_signature = if is_overload(function) {
+        Some(get_input_signature(function)?)
+    } else {
+        None
+    };
+    // Call by the full path, since functions in different modules may share a name
+    let func_name = match &type_arguments {
+        Some(arguments) => format!(
+            "{}::<{}>",
+            function.name,
+            arguments
+                .iter()
+                .map(|a| a.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        None => function.name.clone(),
+    };
+
+    let args = function
+        .inputs
+        .iter()
+        .map(|i| convert_java_type_to_rust(&i.name, &i.rust_type))
+        .collect::<Result<Vec<_>, _>>()?
+        .join(", ");
+    let default = returned_type
+        .map(|o| format!(" {}", get_jni_default_value(o)))
+        .unwrap_or_default();
+    let call = if function.fallible == Some(true) {
+        templates.jni_fallible_call.render(&[
+            ("func_name", &func_name),
+            ("args", &args),
+            ("default", &default),
+        ])
+    } else {
+        format!("{}({})", func_name, args)
+    };
+    let call = if matches!(function.output, Some(RustType::Result(..))) {
+        templates
+            .jni_result_call
+            .render(&[("call", &call), ("default", &default)])
+    } else {
+        call
+    };
+
+    let mut path = get_kotlin_path(function, naming);
+    if wrappers {
+        path.name = get_external_name(&path.name);
+    }
 
-    let params = HashMap::from([
+    // The output and mutable inputs are checked before any of them is converted, since no JNI
+    // call can be made after throwing
+    let mut wide_char_checks = Vec::new();
+    for input in &function.inputs {
+        if matches!(input.rust_type, RustType::Reference { mutable: true, .. }) {
+            wide_char_checks.extend(get_wide_char_check(
+                &format!("{}_value", input.name),
+                &input.rust_type.to_owned_type(),
+            )?);
+        }
+    }
+    if let Some(returned_type) = returned_type {
+        wide_char_checks.extend(get_wide_char_check("result", returned_type)?);
+    }
+    let wide_char_guard = if wide_char_checks.is_empty() {
+        String::new()
+    } else {
+        format!(
+            "\nif {} {{\n    env.throw_new(\"java/lang/IllegalArgumentException\", \"{} produced a char outside the Basic Multilingual Plane, which does not fit in a Java char\").unwrap();\n    return{};\n}}",
+            wide_char_checks.join(" || "),
+            function.name,
+            default
+        )
+    };
+
+    Ok(templates.jni_function.render(&[
+        ("docs", &get_rust_docs(function)),
         (
-            "name".to_string(),
-            get_jni_function_name(java_package, &function.name),
+            "name",
+            &get_jni_function_name(java_package, &path, input_signature.as_deref()),
         ),
-        ("params".to_string(), inputs.join(",\n")),
+        ("params", &inputs.join(",\n")),
         (
-            "ret_type".to_string(),
-            output
+            "setup",
+            &statements(get_input_setup, |s| format!("{}\n", s))?,
+        ),
+        (
+            "write_back",
+            &format!(
+                "{}{}",
+                wide_char_guard,
+                statements(get_input_write_back, |s| format!("\n{}", s))?
+            ),
+        ),
+        (
+            "ret_type",
+            &output
                 .as_ref()
                 .map_or(String::new(), |o| format!(" -> {}", o)),
         ),
         (
-            "result_assignment".to_string(),
+            "result_assignment",
             if output.is_some() {
                 "let result = "
             } else {
                 ""
-            }
-            .to_string(),
-        ),
-        ("func_name".to_string(), unqualified_name.to_string()),
-        (
-            "args".to_string(),
-            function
-                .inputs
-                .as_ref()
-                .unwrap_or(&vec![])
-                .iter()
-                .map(|i| convert_java_type_to_rust(&i.name, &i.rust_type))
-                .collect::<Vec<_>>()
-                .join(", "),
+            },
         ),
+        ("call", &call),
         (
-            "return".to_string(),
-            if output.is_some() {
-                convert_rust_type_to_java("result", function.output.as_ref().unwrap())
-            } else {
-                "".to_string()
+            "return",
+            &match returned_type {
+                Some(o) => format!("\n{}", convert_rust_type_to_java("result", o)?),
+                None => String::new(),
             },
         ),
-    ]);
-
-    populate_template(JNI_FUNCTION_TEMPLATE, &params)
+    ]))
 }
 
-pub fn generate_kotlin_function(function: &Member) -> String {
-    let inputs = function
-        .inputs
+/// Generates the Kotlin declaration of a function, documented with the docs of the function in the
+/// config, or else with `docs` if given, which is a Rust doc comment. With `wrappers`, the function
+/// is a public wrapper of a private external function, along with a variant returning a `Result`
+/// if the function throws. Returns the reason when a type or default value of the function cannot
+/// be converted.
+pub fn generate_kotlin_function(
+    function: &Function,
+    naming: &Naming,
+    docs: Option<&str>,
+    wrappers: bool,
+    templates: &Templates,
+) -> Result<String, String> {
+    let parameters = |with_defaults: bool| {
+        function
+            .inputs
+            .iter()
+            .map(|i| {
+                let default = match &i.default {
+                    Some(value) if with_defaults => {
+                        let value = get_kotlin_default_value(&i.rust_type, value)
+                            .map_err(|reason| format!("invalid default value: {}", reason))?;
+                        format!(" = {}", value)
+                    }
+                    _ => String::new(),
+                };
+                Ok(format!(
+                    "{}: {}{}",
+                    escape_kotlin_name(&get_kotlin_parameter_name(i, naming)),
+                    get_kotlin_input_type(&i.rust_type)?,
+                    default
+                ))
+            })
+            .collect::<Result<Vec<_>, String>>()
+            .map(|parameters| parameters.join(", "))
+    };
+    let inputs = parameters(true)?;
+
+    let returned_type = function.returned_type().map(get_kotlin_type).transpose()?;
+    let output = returned_type
         .as_ref()
-        .unwrap_or(&vec![])
+        .map(|t| format!(": {}", t))
+        .unwrap_or_default();
+
+    let unescaped_name = get_kotlin_path(function, naming).name;
+    let name = escape_kotlin_name(&unescaped_name);
+
+    let inputs_docs = &function.inputs;
+    let docs = function.docs.as_deref().or(docs);
+    let docs = if docs.is_some() || inputs_docs.iter().any(|i| i.docs.is_some()) {
+        let parameters = inputs_docs
+            .iter()
+            .map(|i| DocParameter {
+                name: strip_raw_prefix(&i.name),
+                kotlin_name: get_kotlin_parameter_name(i, naming),
+                docs: i.docs.as_deref(),
+            })
+            .collect::<Vec<_>>();
+        format!("{}\n", to_kdoc(docs.unwrap_or_default(), &parameters))
+    } else {
+        String::new()
+    };
+
+    if !wrappers {
+        return Ok(templates.kotlin_function.render(&[
+            ("docs", &docs),
+            ("name", &name),
+            ("params", &inputs),
+            ("ret_type", &output),
+        ]));
+    }
+
+    let args = function
+        .inputs
         .iter()
-        .map(|i| format!("{}: {}", i.name, get_kotlin_type(&i.rust_type)))
+        .map(|i| escape_kotlin_name(&get_kotlin_parameter_name(i, naming)))
         .collect::<Vec<_>>()
         .join(", ");
+    let values = [
+        ("docs", docs.as_str()),
+        ("name", &name),
+        ("params", &inputs),
+        ("external_params", &parameters(false)?),
+        ("ret_type", &output),
+        (
+            "external_name",
+            &escape_kotlin_name(&get_external_name(&unescaped_name)),
+        ),
+        (
+            "catching_name",
+            &escape_kotlin_name(&format!("{}Catching", unescaped_name)),
+        ),
+        ("result_type", returned_type.as_deref().unwrap_or("Unit")),
+        ("args", &args),
+    ];
 
-    let output = function
-        .output
-        .as_ref()
-        .map(|t| format!(": {}", get_kotlin_type(t)))
+    let wrapper = templates.kotlin_wrapper.render(&values);
+    if function.throws() {
+        Ok(format!(
+            "{}\n\n{}",
+            wrapper,
+            templates.kotlin_catching.render(&values)
+        ))
+    } else {
+        Ok(wrapper)
+    }
+}
+
+/// The `///` comments of the Rust wrapper of a function, from the docs in the config.
+fn get_rust_docs(function: &Function) -> String {
+    let mut lines: Vec<String> = function
+        .docs
+        .as_deref()
+        .map(|docs| docs.lines().map(|l| l.to_string()).collect())
         .unwrap_or_default();
 
-    // let unqualified_name = function.name.split("::").last().unwrap();
-    let module_names = get_modules(&function.name)
+    let inputs = &function.inputs;
+    let documented_inputs = inputs
         .iter()
-        .skip(1)
-        .map(|m| to_pascal_case(m))
-        .collect::<Vec<_>>()
-        .join("_");
-    let function_name = to_camel_case(&get_unqualified_name(&function.name));
+        .filter_map(|i| Some((strip_raw_prefix(&i.name), i.docs.as_ref()?)))
+        .collect::<Vec<_>>();
+    if !documented_inputs.is_empty() {
+        if !lines.is_empty() {
+            lines.push(String::new());
+        }
+        lines.push("# Arguments".to_string());
+        lines.push(String::new());
+        for (name, docs) in documented_inputs {
+            lines.push(format!(
+                "* `{}` - {}",
+                name,
+                docs.lines().collect::<Vec<_>>().join(" ")
+            ));
+        }
+    }
+
+    lines
+        .iter()
+        .map(|l| format!("///{}{}\n", if l.is_empty() { "" } else { " " }, l))
+        .collect()
+}
+
+fn get_kotlin_parameter_name(input: &Input, naming: &Naming) -> String {
+    naming.rename(
+        input
+            .kotlin_name
+            .as_deref()
+            .unwrap_or(strip_raw_prefix(&input.name)),
+    )
+}
+
+/// Finds inputs and outputs whose types cannot be bound, returning an error for each of them.
+pub fn find_unsupported_types(functions: &[Function]) -> Vec<Diagnostic> {
+    let mut errors = Vec::new();
+    for function in functions {
+        for input in &function.inputs {
+            if let Err(reason) = check_type(&input.rust_type, true) {
+                errors.push(
+                    Diagnostic::error(format!(
+                        "unsupported type `{}`: {}",
+                        input.rust_type, reason
+                    ))
+                    .in_member(&function.name)
+                    .in_input(&input.name),
+                );
+            }
+        }
+        if let Some(output) = &function.output
+            && let Err(reason) = check_type(output, false)
+        {
+            errors.push(
+                Diagnostic::error(format!("unsupported output type `{}`: {}", output, reason))
+                    .in_member(&function.name),
+            );
+        }
+    }
+    errors
+}
+
+/// Finds Kotlin function, object and parameter names that cannot be used on the JVM, returning an
+/// error for each of them.
+pub fn find_invalid_kotlin_names(functions: &[Function], naming: &Naming) -> Vec<Diagnostic> {
+    let mut errors = Vec::new();
+    for function in functions {
+        let path = get_kotlin_path(function, naming);
+        let mut names = vec![("object", path.root.clone(), None)];
+        names.extend(path.objects.iter().map(|o| ("object", o.clone(), None)));
+        names.push(("function", path.name.clone(), None));
+        for input in &function.inputs {
+            names.push((
+                "parameter",
+                get_kotlin_parameter_name(input, naming),
+                Some(&input.name),
+            ));
+        }
+
+        for (kind, name, input) in names {
+            if let Err(reason) = check_jvm_name(&name) {
+                let error = Diagnostic::error(format!(
+                    "the Kotlin {} `{}` is not a valid JVM name: {}",
+                    kind, name, reason
+                ))
+                .in_member(&function.name);
+                errors.push(match input {
+                    Some(input) => error.in_input(input),
+                    None => error,
+                });
+            }
+        }
+    }
+    errors
+}
+
+/// Finds default values of inputs that do not fit the type of the input, returning an error for
+/// each of them.
+pub fn find_invalid_defaults(functions: &[Function]) -> Vec<Diagnostic> {
+    let mut errors = Vec::new();
+    for function in functions {
+        for input in &function.inputs {
+            let Some(value) = &input.default else {
+                continue;
+            };
+            // The default value of an unsupported type is reported as an unsupported type
+            if check_type(&input.rust_type, true).is_err() {
+                continue;
+            }
+            if let Err(reason) = get_kotlin_default_value(&input.rust_type, value) {
+                errors.push(
+                    Diagnostic::error(format!("invalid default value: {}", reason))
+                        .in_member(&function.name)
+                        .in_input(&input.name),
+                );
+            }
+        }
+    }
+    errors
+}
+
+/// Finds Kotlin function and object names that are Java keywords, which Java code cannot call,
+/// returning a warning for each of them.
+pub fn find_java_keyword_names(functions: &[Function], naming: &Naming) -> Vec<Diagnostic> {
+    let mut warnings = Vec::new();
+    for function in functions {
+        let path = get_kotlin_path(function, naming);
+        let names = path.objects.iter().chain(std::iter::once(&path.name));
+        for name in names.filter(|n| is_java_keyword(n)) {
+            warnings.push(
+                Diagnostic::warning(format!(
+                    "the Kotlin name `{}` is a Java keyword and cannot be used from Java; add it to \"renames\" to rename it",
+                    name
+                ))
+                .in_member(&function.name),
+            );
+        }
+    }
+    warnings
+}
+
+/// Finds functions that would be declared with the same name on the Kotlin side and cannot be
+/// told apart, returning an error for each collision.
+pub fn find_kotlin_collisions(functions: &[Function], naming: &Naming) -> Vec<Diagnostic> {
+    let mut groups: Vec<(KotlinPath, Vec<&Function>)> = Vec::new();
+    for function in functions {
+        let path = get_kotlin_path(function, naming);
+        match groups.iter_mut().find(|(p, _)| *p == path) {
+            Some((_, group)) => group.push(function),
+            None => groups.push((path, vec![function])),
+        }
+    }
 
-    let name = format!("{}_{}", module_names, function_name);
+    let mut collisions = Vec::new();
+    for (path, group) in groups.iter().filter(|(_, group)| group.len() > 1) {
+        let kotlin_name = path.to_kotlin_string();
 
-    let params = HashMap::from([
-        ("name".to_string(), name.clone()),
-        ("params".to_string(), inputs),
-        ("ret_type".to_string(), output.clone()),
-        ("func_name".to_string(), name),
-    ]);
+        if !group.iter().all(|f| is_overload(f)) {
+            let names = group
+                .iter()
+                .map(|f| format!("`{}`", f.name))
+                .collect::<Vec<_>>();
+            collisions.push(Diagnostic::error(format!(
+                "{} and {} are bound to the same Kotlin function `{}`; rename them, or mark each of them with \"overload\": true",
+                names[..names.len() - 1].join(", "),
+                names[names.len() - 1],
+                kotlin_name
+            )));
+            continue;
+        }
 
-    populate_template(KOTLIN_FUNCTION_TEMPLATE, &params)
+        for (i, function) in group.iter().enumerate() {
+            // Unsupported types are reported on their own
+            let Ok(signature) = get_input_signature(function) else {
+                continue;
+            };
+            if let Some(other) = group[..i]
+                .iter()
+                .find(|other| get_input_signature(other).as_ref() == Ok(&signature))
+            {
+                collisions.push(
+                    Diagnostic::error(format!(
+                        "`{}` and `{}` are both bound to the Kotlin function `{}` with the same parameter types ({}); overloads must differ in their parameter types",
+                        other.name, function.name, kotlin_name, signature
+                    ))
+                    .in_member(&function.name),
+                );
+            }
+        }
+    }
+    collisions
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::config::Input;
+    use crate::rust_type::parse_type;
+    use std::collections::HashMap;
 
     use super::*;
     use rstest::rstest;
 
+    fn input(name: &str, rust_type: &str) -> Input {
+        Input {
+            name: name.to_string(),
+            rust_type: parse_type(rust_type).unwrap(),
+            kotlin_name: None,
+            docs: None,
+            default: None,
+        }
+    }
+
+    /// A function with inputs of the given names and types.
+    fn function_with(name: &str, inputs: &[(&str, &str)], output: Option<&str>) -> Function {
+        Function {
+            name: name.to_string(),
+            inputs: inputs.iter().map(|(name, t)| input(name, t)).collect(),
+            output: output.map(|o| parse_type(o).unwrap()),
+            generics: None,
+            instantiations: None,
+            overload: None,
+            fallible: None,
+            kotlin_name: None,
+            kotlin_object: None,
+            docs: None,
+        }
+    }
+
+    /// A function with a single input named `value`.
+    fn function(name: &str, input: &str, overload: Option<bool>) -> Function {
+        Function {
+            overload,
+            ..function_with(name, &[("value", input)], None)
+        }
+    }
+
+    fn jni_function_name(package: &str, name: &str, input_signature: Option<&str>) -> String {
+        let path = get_kotlin_path(&function(name, "f32", None), &Naming::default());
+        get_jni_function_name(package, &path, input_signature)
+    }
+
     #[rstest]
     #[case("com.example", "test::func", "Java_com_example_Test_func")]
     #[case("com.example_test", "test::func", "Java_com_example_1test_Test_func")]
-    #[case("com.example", "mod1::mod2::func", "Java_com_example_Mod1_Mod2_func")]
+    #[case(
+        "com.example",
+        "mod1::mod2::func",
+        "Java_com_example_Mod1_00024Mod2_func"
+    )]
     fn test_jni_function_name(#[case] package: &str, #[case] name: &str, #[case] expected: &str) {
-        assert_eq!(get_jni_function_name(package, name), expected);
+        assert_eq!(jni_function_name(package, name, None), expected);
+    }
+
+    #[rstest]
+    #[case("[F", "Java_com_example_Test_mean___3F")]
+    #[case(
+        "Ljava/lang/String;I",
+        "Java_com_example_Test_mean__Ljava_lang_String_2I"
+    )]
+    #[case("", "Java_com_example_Test_mean__")]
+    fn test_jni_function_long_name(#[case] signature: &str, #[case] expected: &str) {
+        assert_eq!(
+            jni_function_name("com.example", "test::mean", Some(signature)),
+            expected
+        );
     }
 
     #[test]
     fn test_generate_jni_function() {
-        let function = Member {
-            member_type: "function".to_string(),
-            name: "test::func".to_string(),
-            inputs: Some(vec![Input {
-                name: "arg1".to_string(),
-                rust_type: "i32".to_string(),
-            }]),
-            output: Some("bool".to_string()),
-        };
+        let function = function_with("test::func", &[("arg1", "i32")], Some("bool"));
 
         let expected = r#"#[unsafe(no_mangle)]
 pub extern "C" fn Java_com_example_Test_func(
@@ -172,10 +695,783 @@ pub extern "C" fn Java_com_example_Test_func(
     _: JClass,
     arg1: jint
 ) -> jboolean {
-    let result = func(arg1);
+    let result = test::func(arg1);
+    jboolean::from(result)
+}"#;
+
+        assert_eq!(
+            generate_jni_function(
+                "com.example",
+                &function,
+                &Naming::default(),
+                false,
+                &Templates::default()
+            )
+            .unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_generate_jni_function_with_borrowed_inputs() {
+        let mut sum = function("test::sum", "&[u8]", None);
+        sum.output = Some(parse_type("u64").unwrap());
+        let mut label = function("test::label", "&str", None);
+        label.output = Some(parse_type("&str").unwrap());
+
+        let expected_sum = r#"#[unsafe(no_mangle)]
+pub extern "C" fn Java_com_example_Test_sum(
+    mut env: JNIEnv,
+    _: JClass,
+    value: JByteArray
+) -> jlong {
+    let value_value: Vec<u8> = {
+        let length = env.get_array_length(&value).unwrap();
+        let mut buffer = vec![jbyte::default(); length as usize];
+        env.get_byte_array_region(&value, 0, &mut buffer).unwrap();
+        buffer.into_iter().map(|value| value as u8).collect()
+    };
+    let result = test::sum(&value_value);
+    result as i64
+}"#;
+        // The result borrows from the local, which lives until the result is converted
+        let expected_label = r#"#[unsafe(no_mangle)]
+pub extern "C" fn Java_com_example_Test_label(
+    mut env: JNIEnv,
+    _: JClass,
+    value: JString
+) -> jstring {
+    let value_value: String = String::from(env.get_string(&value).unwrap());
+    let result = test::label(&value_value);
+    env.new_string(result.to_owned()).unwrap().into_raw()
+}"#;
+
+        let generate = |function| {
+            generate_jni_function(
+                "com.example",
+                function,
+                &Naming::default(),
+                false,
+                &Templates::default(),
+            )
+            .unwrap()
+        };
+        assert_eq!(generate(&sum), expected_sum);
+        assert_eq!(generate(&label), expected_label);
+    }
+
+    #[test]
+    fn test_generate_jni_function_with_mutable_reference() {
+        let function = function_with(
+            "test::scale",
+            &[("values", "&mut [f32]"), ("factor", "f32")],
+            None,
+        );
+
+        let expected = r#"#[unsafe(no_mangle)]
+pub extern "C" fn Java_com_example_Test_scale(
+    mut env: JNIEnv,
+    _: JClass,
+    values: JFloatArray,
+    factor: jfloat
+) {
+    let mut values_value: Vec<f32> = {
+        let length = env.get_array_length(&values).unwrap();
+        let mut buffer = vec![jfloat::default(); length as usize];
+        env.get_float_array_region(&values, 0, &mut buffer).unwrap();
+        buffer
+    };
+    test::scale(&mut values_value, factor);
+    env.set_float_array_region(&values, 0, &values_value).unwrap();
+}"#;
+
+        assert_eq!(
+            generate_jni_function(
+                "com.example",
+                &function,
+                &Naming::default(),
+                false,
+                &Templates::default()
+            )
+            .unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_generate_jni_function_with_wide_chars() {
+        let function = function_with("test::initial", &[("values", "&mut [char]")], Some("char"));
+
+        let expected = r#"#[unsafe(no_mangle)]
+pub extern "C" fn Java_com_example_Test_initial(
+    mut env: JNIEnv,
+    _: JClass,
+    values: JCharArray
+) -> jchar {
+    let mut values_value: Vec<char> = {
+        let length = env.get_array_length(&values).unwrap();
+        let mut buffer = vec![jchar::default(); length as usize];
+        env.get_char_array_region(&values, 0, &mut buffer).unwrap();
+        buffer.into_iter().map(|value| char::from_u32(value.into()).unwrap_or(char::REPLACEMENT_CHARACTER)).collect()
+    };
+    let result = test::initial(&mut values_value);
+    if values_value.iter().any(|value| u32::from(*value) > 0xFFFF) || u32::from(result) > 0xFFFF {
+        env.throw_new("java/lang/IllegalArgumentException", "test::initial produced a char outside the Basic Multilingual Plane, which does not fit in a Java char").unwrap();
+        return Default::default();
+    }
+    env.set_char_array_region(&values, 0, &values_value.into_iter().map(|value| value as jchar).collect::<Vec<_>>()).unwrap();
+    result as jchar
+}"#;
+
+        assert_eq!(
+            generate_jni_function(
+                "com.example",
+                &function,
+                &Naming::default(),
+                false,
+                &Templates::default()
+            )
+            .unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_generate_kotlin_function_with_mutable_reference() {
+        let function = function_with("test::increment", &[("count", "&mut i32")], None);
+
+        assert_eq!(
+            generate_kotlin_function(
+                &function,
+                &Naming::default(),
+                None,
+                false,
+                &Templates::default()
+            )
+            .unwrap(),
+            "external fun increment(count: IntArray)"
+        );
+    }
+
+    #[test]
+    fn test_generate_jni_function_generic() {
+        let function = Function {
+            generics: Some(vec!["T".to_string()]),
+            instantiations: Some(vec![
+                HashMap::from([("T".to_string(), parse_type("f32").unwrap())]),
+                HashMap::from([("T".to_string(), parse_type("f64").unwrap())]),
+            ]),
+            ..function_with("test::mean", &[("values", "&[T]")], Some("T"))
+        };
+        let members = function.monomorphize().unwrap();
+
+        let expected = r#"#[unsafe(no_mangle)]
+pub extern "C" fn Java_com_example_Test_mean___3D(
+    mut env: JNIEnv,
+    _: JClass,
+    values: JDoubleArray
+) -> jdouble {
+    let values_value: Vec<f64> = {
+        let length = env.get_array_length(&values).unwrap();
+        let mut buffer = vec![jdouble::default(); length as usize];
+        env.get_double_array_region(&values, 0, &mut buffer).unwrap();
+        buffer
+    };
+    let result = test::mean::<f64>(&values_value);
     result
 }"#;
 
-        assert_eq!(generate_jni_function("com.example", &function), expected);
+        assert_eq!(
+            generate_jni_function(
+                "com.example",
+                &members[1],
+                &Naming::default(),
+                false,
+                &Templates::default()
+            )
+            .unwrap(),
+            expected
+        );
+        assert_eq!(
+            generate_kotlin_function(
+                &members[0],
+                &Naming::default(),
+                None,
+                false,
+                &Templates::default()
+            )
+            .unwrap(),
+            "external fun mean(values: FloatArray): Float"
+        );
+        assert_eq!(
+            generate_kotlin_function(
+                &members[1],
+                &Naming::default(),
+                None,
+                false,
+                &Templates::default()
+            )
+            .unwrap(),
+            "external fun mean(values: DoubleArray): Double"
+        );
+    }
+
+    #[test]
+    fn test_find_kotlin_collisions_none() {
+        let functions = vec![
+            function("test::a::func", "f32", None),
+            function("test::b::func", "f32", None),
+        ];
+        assert!(find_kotlin_collisions(&functions, &Naming::default()).is_empty());
+    }
+
+    #[test]
+    fn test_find_kotlin_collisions_same_name() {
+        let functions = vec![
+            function("test::my_module::func", "f32", None),
+            function("test::MY_MODULE::func", "f64", None),
+            function("test::my_module::func", "i32", Some(true)),
+        ];
+        assert_eq!(
+            find_kotlin_collisions(&functions, &Naming::default()),
+            vec![Diagnostic::error(
+                "`test::my_module::func`, `test::MY_MODULE::func` and `test::my_module::func` are bound to the same Kotlin function `MyModule.func`; rename them, or mark each of them with \"overload\": true"
+            )]
+        );
+    }
+
+    #[test]
+    fn test_find_kotlin_collisions_overloads() {
+        let functions = vec![
+            function("test::a::func", "f32", Some(true)),
+            function("test::A::func", "&[f32]", Some(true)),
+            function("test::a::other", "f32", None),
+        ];
+        assert!(find_kotlin_collisions(&functions, &Naming::default()).is_empty());
+        assert_eq!(
+            generate_jni_function(
+                "com.example",
+                &functions[0],
+                &Naming::default(),
+                false,
+                &Templates::default()
+            )
+            .unwrap()
+            .lines()
+            .nth(1)
+            .unwrap(),
+            r#"pub extern "C" fn Java_com_example_Test_00024A_func__F("#
+        );
+    }
+
+    #[test]
+    fn test_find_kotlin_collisions_overloads_with_same_parameters() {
+        let functions = vec![
+            function("test::a::func", "HashMap<String, i32>", Some(true)),
+            function("test::A::func", "HashMap<String, i64>", Some(true)),
+        ];
+        assert_eq!(
+            find_kotlin_collisions(&functions, &Naming::default()),
+            vec![
+                Diagnostic::error(
+                    "`test::a::func` and `test::A::func` are both bound to the Kotlin function `A.func` with the same parameter types (Ljava/util/Map;); overloads must differ in their parameter types"
+                )
+                .in_member("test::A::func")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_find_kotlin_collisions_overloads_with_unsupported_types() {
+        let functions = vec![
+            function("test::a::func", "Point", Some(true)),
+            function("test::A::func", "Point", Some(true)),
+        ];
+        assert!(find_kotlin_collisions(&functions, &Naming::default()).is_empty());
+    }
+
+    #[test]
+    fn test_generate_function_with_unsupported_type() {
+        let point = function("my_crate::point", "Vec<Point>", None);
+        let templates = Templates::default();
+        assert_eq!(
+            generate_jni_function("com.example", &point, &Naming::default(), false, &templates),
+            Err("`Point` is not a supported type".to_string())
+        );
+        assert_eq!(
+            generate_kotlin_function(&point, &Naming::default(), None, false, &templates),
+            Err("`Point` is not a supported type".to_string())
+        );
+    }
+
+    #[test]
+    fn test_get_kotlin_path() {
+        let naming = Naming {
+            acronyms: Some(vec!["GPS".to_string(), "NMEA".to_string()]),
+            strip_prefixes: Some(vec!["ffi_".to_string()]),
+            renames: None,
+        };
+        assert_eq!(
+            get_kotlin_path(
+                &function("my_crate::gps::ffi_parse_gps_nmea", "f32", None),
+                &naming
+            ),
+            KotlinPath {
+                root: "MyCrate".to_string(),
+                objects: vec!["GPS".to_string()],
+                name: "parseGPSNMEA".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_get_kotlin_path_overrides() {
+        let mut nested = function("my_crate::gps::parse_gps_nmea", "f32", None);
+        nested.kotlin_name = Some("parse_nmea".to_string());
+        nested.kotlin_object = Some("Location.Gps".to_string());
+        let mut root = function("my_crate::gps::parse_gps_nmea", "f32", None);
+        root.kotlin_object = Some("".to_string());
+
+        let naming = Naming::default();
+        assert_eq!(
+            get_kotlin_path(&nested, &naming),
+            KotlinPath {
+                root: "MyCrate".to_string(),
+                objects: vec!["Location".to_string(), "Gps".to_string()],
+                name: "parse_nmea".to_string(),
+            }
+        );
+        assert_eq!(
+            get_jni_function_name("com.example", &get_kotlin_path(&nested, &naming), None),
+            "Java_com_example_MyCrate_00024Location_00024Gps_parse_1nmea"
+        );
+        assert_eq!(
+            get_kotlin_path(&root, &naming).objects,
+            Vec::<String>::new()
+        );
+    }
+
+    #[test]
+    fn test_generate_kotlin_function_with_parameter_name() {
+        let mut renamed = function("my_crate::gps::parse", "&str", None);
+        renamed.inputs[0].kotlin_name = Some("sentence".to_string());
+        renamed.kotlin_name = Some("parseSentence".to_string());
+        assert_eq!(
+            generate_kotlin_function(
+                &renamed,
+                &Naming::default(),
+                None,
+                false,
+                &Templates::default()
+            )
+            .unwrap(),
+            "external fun parseSentence(sentence: String)"
+        );
+    }
+
+    #[test]
+    fn test_generate_kotlin_function_with_keywords() {
+        let mut keywords = function("my_crate::object::when", "f32", None);
+        keywords.inputs[0].name = "fun".to_string();
+        assert_eq!(
+            generate_kotlin_function(
+                &keywords,
+                &Naming::default(),
+                None,
+                false,
+                &Templates::default()
+            )
+            .unwrap(),
+            "external fun `when`(`fun`: Float)"
+        );
+
+        let naming = Naming {
+            renames: Some(HashMap::from([
+                ("fun".to_string(), "function".to_string()),
+                ("when".to_string(), "whenever".to_string()),
+            ])),
+            ..Default::default()
+        };
+        assert_eq!(
+            generate_kotlin_function(&keywords, &naming, None, false, &Templates::default())
+                .unwrap(),
+            "external fun whenever(function: Float)"
+        );
+        assert_eq!(
+            jni_function_name("com.example", "my_crate::object::when", None),
+            "Java_com_example_MyCrate_00024Object_when"
+        );
+    }
+
+    #[test]
+    fn test_generate_kotlin_function_with_raw_identifier() {
+        let mut raw = function("my_crate::kind", "i32", None);
+        raw.inputs[0].name = "r#type".to_string();
+        let docs = "The kind.\n\n# Arguments\n\n* `type` - A type.";
+        assert_eq!(
+            generate_kotlin_function(
+                &raw,
+                &Naming::default(),
+                Some(docs),
+                false,
+                &Templates::default()
+            )
+            .unwrap(),
+            r#"/**
+ * The kind.
+ *
+ * @param type A type.
+ */
+external fun kind(type: Int)"#
+        );
+    }
+
+    #[test]
+    fn test_find_invalid_kotlin_names() {
+        let mut invalid = function("my_crate::parse", "f32", None);
+        invalid.kotlin_name = Some("parse<T>".to_string());
+        invalid.kotlin_object = Some("Gps..Nmea".to_string());
+        invalid.inputs[0].kotlin_name = Some("".to_string());
+        let functions = vec![invalid, function("my_crate::valid", "f32", None)];
+        assert_eq!(
+            find_invalid_kotlin_names(&functions, &Naming::default()),
+            vec![
+                Diagnostic::error(
+                    "the Kotlin function `parse<T>` is not a valid JVM name: names cannot contain '<'"
+                )
+                .in_member("my_crate::parse"),
+                Diagnostic::error(
+                    "the Kotlin parameter `` is not a valid JVM name: names cannot be empty"
+                )
+                .in_member("my_crate::parse")
+                .in_input("value"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_find_java_keyword_names() {
+        let functions = vec![
+            function("my_crate::native::new", "f32", None),
+            function("my_crate::geo::distance", "f32", None),
+        ];
+        assert_eq!(
+            find_java_keyword_names(&functions, &Naming::default()),
+            vec![
+                Diagnostic::warning(
+                    "the Kotlin name `new` is a Java keyword and cannot be used from Java; add it to \"renames\" to rename it"
+                )
+                .in_member("my_crate::native::new")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_generate_kotlin_function_with_docs() {
+        let mut documented = function("my_crate::geo::distance", "f64", None);
+        documented.inputs[0].kotlin_name = Some("meters".to_string());
+        let docs = "The distance to `value`.\n\n# Arguments\n\n* `value` - A distance.";
+        assert_eq!(
+            generate_kotlin_function(
+                &documented,
+                &Naming::default(),
+                Some(docs),
+                false,
+                &Templates::default()
+            )
+            .unwrap(),
+            r#"/**
+ * The distance to `value`.
+ *
+ * @param meters A distance.
+ */
+external fun distance(meters: Double)"#
+        );
+    }
+
+    #[test]
+    fn test_generate_function_with_config_docs() {
+        let mut documented = function("my_crate::geo::distance", "f64", None);
+        documented.docs = Some("The distance in meters.".to_string());
+        documented.inputs[0].docs = Some("A distance in feet.".to_string());
+
+        let jni_function = generate_jni_function(
+            "com.example",
+            &documented,
+            &Naming::default(),
+            false,
+            &Templates::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            jni_function.lines().take(5).collect::<Vec<_>>(),
+            vec![
+                "/// The distance in meters.",
+                "///",
+                "/// # Arguments",
+                "///",
+                "/// * `value` - A distance in feet.",
+            ]
+        );
+        assert_eq!(
+            generate_kotlin_function(
+                &documented,
+                &Naming::default(),
+                Some("From the source."),
+                false,
+                &Templates::default()
+            )
+            .unwrap(),
+            r#"/**
+ * The distance in meters.
+ *
+ * @param value A distance in feet.
+ */
+external fun distance(value: Double)"#
+        );
+    }
+
+    #[test]
+    fn test_generate_kotlin_function_with_wrappers() {
+        let mut distance = function("my_crate::geo::distance", "f64", None);
+        distance.output = Some(parse_type("f64").unwrap());
+        assert_eq!(
+            generate_kotlin_function(
+                &distance,
+                &Naming::default(),
+                Some("The distance."),
+                true,
+                &Templates::default()
+            )
+            .unwrap(),
+            r#"/**
+ * The distance.
+ */
+fun distance(value: Double): Double = distanceNative(value)
+
+private external fun distanceNative(value: Double): Double"#
+        );
+        assert_eq!(
+            jni_function_name("com.example", "my_crate::geo::distance", None),
+            "Java_com_example_MyCrate_00024Geo_distance"
+        );
+        assert!(
+            generate_jni_function(
+                "com.example",
+                &distance,
+                &Naming::default(),
+                true,
+                &Templates::default()
+            )
+            .unwrap()
+            .contains("fn Java_com_example_MyCrate_00024Geo_distanceNative(")
+        );
+    }
+
+    #[test]
+    fn test_generate_fallible_function() {
+        let mut when = function("my_crate::when", "i32", None);
+        when.inputs[0].name = "in".to_string();
+        when.fallible = Some(true);
+        assert_eq!(
+            generate_kotlin_function(&when, &Naming::default(), None, true, &Templates::default())
+                .unwrap(),
+            r#"fun `when`(`in`: Int) = whenNative(`in`)
+
+private external fun whenNative(`in`: Int)
+
+/** Calls [`when`], returning the exception it throws as a failed [Result]. */
+fun whenCatching(`in`: Int): Result<Unit> = runCatching { `when`(`in`) }"#
+        );
+
+        let mut parse = function("my_crate::parse", "&str", None);
+        parse.output = Some(parse_type("String").unwrap());
+        parse.fallible = Some(true);
+        let jni_function = generate_jni_function(
+            "com.example",
+            &parse,
+            &Naming::default(),
+            false,
+            &Templates::default(),
+        )
+        .unwrap();
+        assert!(jni_function.contains(
+            "let result = match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| my_crate::parse("
+        ));
+        assert!(jni_function.contains("return std::ptr::null_mut();"));
+    }
+
+    #[test]
+    fn test_generate_result_function() {
+        let parse = function_with(
+            "my_crate::parse",
+            &[("text", "&str")],
+            Some("Result<i32, my_crate::ParseError>"),
+        );
+        assert_eq!(
+            generate_jni_function(
+                "com.example",
+                &parse,
+                &Naming::default(),
+                false,
+                &Templates::default()
+            )
+            .unwrap(),
+            r#"#[unsafe(no_mangle)]
+pub extern "C" fn Java_com_example_MyCrate_parse(
+    mut env: JNIEnv,
+    _: JClass,
+    text: JString
+) -> jint {
+    let text_value: String = String::from(env.get_string(&text).unwrap());
+    let result = match my_crate::parse(&text_value) {
+        Ok(value) => value,
+        Err(error) => {
+            env.throw_new("java/lang/RuntimeException", error.to_string()).unwrap();
+            return Default::default();
+        }
+    };
+    result
+}"#
+        );
+        assert_eq!(
+            generate_kotlin_function(
+                &parse,
+                &Naming::default(),
+                None,
+                true,
+                &Templates::default()
+            )
+            .unwrap(),
+            r#"fun parse(text: String): Int = parseNative(text)
+
+private external fun parseNative(text: String): Int
+
+/** Calls [parse], returning the exception it throws as a failed [Result]. */
+fun parseCatching(text: String): Result<Int> = runCatching { parse(text) }"#
+        );
+
+        let mut save = function_with("my_crate::save", &[], Some("Result<(), String>"));
+        save.fallible = Some(true);
+        let jni_function = generate_jni_function(
+            "com.example",
+            &save,
+            &Naming::default(),
+            false,
+            &Templates::default(),
+        )
+        .unwrap();
+        // A fallible function returning a Result throws both its panics and its errors
+        assert_eq!(
+            jni_function,
+            r#"#[unsafe(no_mangle)]
+pub extern "C" fn Java_com_example_MyCrate_save(
+    mut env: JNIEnv,
+    _: JClass
+) {
+    match match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| my_crate::save())) {
+        Ok(result) => result,
+        Err(panic) => {
+            let message = panic
+                .downcast_ref::<&str>()
+                .map(|m| m.to_string())
+                .or_else(|| panic.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "my_crate::save panicked".to_string());
+            env.throw_new("java/lang/RuntimeException", message).unwrap();
+            return;
+        }
+    } {
+        Ok(value) => value,
+        Err(error) => {
+            env.throw_new("java/lang/RuntimeException", error.to_string()).unwrap();
+            return;
+        }
+    };
+}"#
+        );
+        assert_eq!(
+            generate_kotlin_function(
+                &save,
+                &Naming::default(),
+                None,
+                false,
+                &Templates::default()
+            )
+            .unwrap(),
+            "external fun save()"
+        );
+    }
+
+    #[test]
+    fn test_generate_kotlin_function_with_defaults() {
+        let mut scale = function("my_crate::scale", "f32", None);
+        let inputs = &mut scale.inputs;
+        inputs[0].default = Some(serde_json::json!(1));
+        inputs.push(Input {
+            name: "label".to_string(),
+            rust_type: parse_type("&str").unwrap(),
+            kotlin_name: None,
+            docs: None,
+            default: Some(serde_json::json!("$x")),
+        });
+        assert_eq!(
+            generate_kotlin_function(
+                &scale,
+                &Naming::default(),
+                None,
+                false,
+                &Templates::default()
+            )
+            .unwrap(),
+            r#"external fun scale(value: Float = 1.0f, label: String = "\$x")"#
+        );
+        assert_eq!(
+            generate_kotlin_function(
+                &scale,
+                &Naming::default(),
+                None,
+                true,
+                &Templates::default()
+            )
+            .unwrap(),
+            r#"fun scale(value: Float = 1.0f, label: String = "\$x") = scaleNative(value, label)
+
+private external fun scaleNative(value: Float, label: String)"#
+        );
+    }
+
+    #[test]
+    fn test_find_invalid_defaults() {
+        let mut valid = function("my_crate::valid", "bool", None);
+        valid.inputs[0].default = Some(serde_json::json!(false));
+        let mut invalid = function("my_crate::invalid", "u8", None);
+        invalid.inputs[0].default = Some(serde_json::json!(256));
+        assert_eq!(
+            find_invalid_defaults(&[valid, invalid]),
+            vec![
+                Diagnostic::error("invalid default value: 256 is out of the range of `u8`")
+                    .in_member("my_crate::invalid")
+                    .in_input("value")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_find_unsupported_types() {
+        let mut point = function("my_crate::move_point", "Vec<Point>", None);
+        point.output = Some(parse_type("&mut f32").unwrap());
+        let functions = vec![point, function("my_crate::valid", "&mut [f32]", None)];
+        assert_eq!(
+            find_unsupported_types(&functions),
+            vec![
+                Diagnostic::error("unsupported type `Vec<Point>`: `Point` is not a supported type, and structs and enums cannot be bound yet")
+                    .in_member("my_crate::move_point")
+                    .in_input("value"),
+                Diagnostic::error(
+                    "unsupported output type `&mut f32`: mutable references cannot be returned"
+                )
+                .in_member("my_crate::move_point"),
+            ]
+        );
     }
 }
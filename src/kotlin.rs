@@ -1,6 +1,14 @@
 use std::collections::HashMap;
 
-const KOTLIN_ROOT_OBJECT_TEMPLATE: &str = r#"package {package_name};
+use crate::{
+    config::{Crate, Function},
+    error::Diagnostic,
+    functions::{generate_kotlin_function, get_kotlin_path},
+    names::escape_kotlin_name,
+    template::{Template, Templates},
+};
+
+pub const KOTLIN_ROOT_OBJECT_TEMPLATE: &str = r#"package {package_name};
 
 object {name} {
     init {
@@ -10,10 +18,9 @@ object {name} {
     {contents}
 }
 "#;
-const KOTLIN_OBJECT_TEMPLATE: &str = r#"object {name} {
+pub const KOTLIN_OBJECT_TEMPLATE: &str = r#"object {name} {
     {contents}
-}
-"#;
+}"#;
 
 struct KotlinObject {
     name: String,
@@ -21,137 +28,340 @@ struct KotlinObject {
     child_objects: Vec<KotlinObject>,
 }
 
-pub fn get_name_components(binding: &String) -> Vec<String> {
-    match binding.split("fun ").nth(1) {
-        Some(after_fun) => match after_fun.split("(").nth(0) {
-            Some(name) => name.split("_").map(|s| s.to_string()).collect(),
-            None => Vec::new(),
-        },
-        None => Vec::new(),
+impl KotlinObject {
+    fn new(name: &str) -> KotlinObject {
+        KotlinObject {
+            name: name.to_string(),
+            members: Vec::new(),
+            child_objects: Vec::new(),
+        }
+    }
+
+    /// Adds a member to the object at `path` below this one, creating the objects that do not
+    /// exist yet, so every path has a single object.
+    fn insert(&mut self, path: &[String], member: String) {
+        let Some((name, rest)) = path.split_first() else {
+            self.members.push(member);
+            return;
+        };
+        let index = match self.child_objects.iter().position(|o| o.name == *name) {
+            Some(index) => index,
+            None => {
+                self.child_objects.push(KotlinObject::new(name));
+                self.child_objects.len() - 1
+            }
+        };
+        self.child_objects[index].insert(rest, member);
     }
 }
 
-pub fn replace_name(binding: &String, new_name: &String) -> String {
-    let components = get_name_components(binding);
-    binding.replace(&components.join("_"), new_name)
+/// Generates the Kotlin file of a crate from its monomorphized functions, documented with the
+/// `docs` of the crate source. Returns the name of the root object, which names the file, and
+/// the contents of the file, which only has the functions of this crate, or an error for each
+/// function that cannot be generated.
+pub fn generate_crate_kotlin_file(
+    package: &Crate,
+    functions: &[Function],
+    docs: &HashMap<String, String>,
+    java_package: &str,
+    library_name: &str,
+    templates: &Templates,
+) -> Result<(String, String), Vec<Diagnostic>> {
+    let naming = package.naming.clone().unwrap_or_default();
+    let wrappers = package.kotlin_wrappers.unwrap_or_default();
+    let mut bindings = Vec::new();
+    let mut errors = Vec::new();
+    for function in functions {
+        match generate_kotlin_function(
+            function,
+            &naming,
+            docs.get(&function.name).map(String::as_str),
+            wrappers,
+            templates,
+        ) {
+            Ok(binding) => bindings.push((get_kotlin_path(function, &naming).objects, binding)),
+            Err(reason) => errors.push(
+                Diagnostic::error(format!("cannot generate the Kotlin function: {}", reason))
+                    .in_crate(&package.name)
+                    .in_member(&function.name),
+            ),
+        }
+    }
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    let root_object_name = naming.to_pascal_case(&package.name);
+    let contents = generate_kotlin_file(
+        &root_object_name,
+        java_package,
+        library_name,
+        &bindings,
+        templates,
+    );
+    Ok((root_object_name, contents))
 }
 
+/// Generates the Kotlin file of a crate, with each binding in the nested object at its path.
+/// Objects are in the order of their first binding, so the file only changes with the bindings.
 pub fn generate_kotlin_file(
     root_object_name: &str,
     package_name: &str,
     library_name: &str,
-    bindings: &Vec<String>,
+    bindings: &[(Vec<String>, String)],
+    templates: &Templates,
 ) -> String {
-    let mut root = KotlinObject {
-        name: root_object_name.to_string(),
-        members: Vec::new(),
-        child_objects: Vec::new(),
-    };
-
-    // Create nested object hierarchy from bindings
-    let mut object_map: HashMap<Vec<String>, Vec<String>> = HashMap::new();
-    for binding in bindings {
-        let components = get_name_components(binding);
-        if components.len() > 1 {
-            let key = components[..components.len() - 1].to_vec();
-            object_map
-                .entry(key)
-                .or_insert(Vec::new())
-                .push(binding.clone());
-        } else {
-            root.members.push(binding.clone());
+    let mut root = KotlinObject::new(root_object_name);
+    for (path, binding) in bindings {
+        root.insert(path, binding.clone());
+    }
+
+    // Members come first, followed by the nested objects, each separated by a blank line
+    fn get_contents(obj: &KotlinObject, template: &Template) -> String {
+        let mut parts = Vec::new();
+        if !obj.members.is_empty() {
+            // Members spanning several lines are separated by blank lines too
+            let separator = if obj.members.iter().any(|m| m.contains('\n')) {
+                "\n\n"
+            } else {
+                "\n"
+            };
+            parts.push(obj.members.join(separator));
         }
+        parts.extend(obj.child_objects.iter().map(|o| render_object(o, template)));
+        parts.join("\n\n")
+    }
+
+    fn render_object(obj: &KotlinObject, template: &Template) -> String {
+        template.render(&[
+            ("name", &escape_kotlin_name(&obj.name)),
+            ("contents", &get_contents(obj, template)),
+        ])
     }
 
-    // Create nested objects from grouped bindings
-    for (path, group) in object_map {
-        let mut current = &mut root;
-        let mut child_index = None;
+    templates.kotlin_root_object.render(&[
+        ("package_name", package_name),
+        ("name", &escape_kotlin_name(&root.name)),
+        ("library_name", library_name),
+        ("contents", &get_contents(&root, &templates.kotlin_object)),
+    ])
+}
 
-        // Create/traverse object hierarchy for this path
-        for component in &path[..path.len() - 1] {
-            if let Some(index) = child_index {
-                current = &mut current.child_objects[index];
-            }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Member;
+    use rstest::rstest;
 
-            child_index = current
-                .child_objects
-                .iter()
-                .position(|o| o.name == *component);
-
-            if child_index.is_none() {
-                current.child_objects.push(KotlinObject {
-                    name: component.clone(),
-                    members: Vec::new(),
-                    child_objects: Vec::new(),
-                });
-                child_index = Some(current.child_objects.len() - 1);
-            }
-        }
+    /// Bindings of empty functions, at paths such as `A::B`.
+    fn bindings(functions: &[(&str, &str)]) -> Vec<(Vec<String>, String)> {
+        functions
+            .iter()
+            .map(|(path, name)| {
+                (
+                    path.split("::")
+                        .filter(|c| !c.is_empty())
+                        .map(str::to_string)
+                        .collect(),
+                    format!("external fun {}()", name),
+                )
+            })
+            .collect()
+    }
 
-        if let Some(index) = child_index {
-            current = &mut current.child_objects[index];
+    #[test]
+    fn test_generate_kotlin_file() {
+        let bindings = vec![
+            (vec![], "external fun add(a: Int, b: Int): Int".to_string()),
+            (
+                vec!["Geo".to_string(), "Gps".to_string()],
+                "/**\n * Parses a sentence.\n */\nexternal fun parse(sentence: String)".to_string(),
+            ),
+        ];
+        assert_eq!(
+            generate_kotlin_file(
+                "MyCrate",
+                "com.example",
+                "mylib",
+                &bindings,
+                &Templates::default()
+            ),
+            r#"package com.example;
+
+object MyCrate {
+    init {
+        System.loadLibrary("mylib")
+    }
+
+    external fun add(a: Int, b: Int): Int
+
+    object Geo {
+        object Gps {
+            /**
+             * Parses a sentence.
+             */
+            external fun parse(sentence: String)
         }
+    }
+}
+"#
+        );
+    }
 
-        // Create the leaf object
-        let leaf_name = &path[path.len() - 1];
-        let mut leaf = KotlinObject {
-            name: leaf_name.clone(),
-            members: Vec::new(),
-            child_objects: Vec::new(),
+    #[test]
+    fn test_generate_kotlin_file_is_deterministic() {
+        let bindings = ["Zeta", "Alpha", "Mid", "Beta", "Omega", "Gamma"]
+            .iter()
+            .map(|name| {
+                (
+                    vec![name.to_string()],
+                    format!("external fun {}()", name.to_lowercase()),
+                )
+            })
+            .collect::<Vec<_>>();
+        let generate = || {
+            generate_kotlin_file(
+                "MyCrate",
+                "com.example",
+                "mylib",
+                &bindings,
+                &Templates::default(),
+            )
         };
 
-        // Process each binding in the group
-        for binding in group {
-            let components = get_name_components(&binding);
-            let new_name = components.last().unwrap().to_string();
-            let modified_binding = replace_name(&binding, &new_name);
-            leaf.members.push(modified_binding);
+        let contents = generate();
+        for _ in 0..10 {
+            assert_eq!(generate().as_bytes(), contents.as_bytes());
         }
-
-        current.child_objects.push(leaf);
+        let positions = ["Zeta", "Alpha", "Mid", "Beta", "Omega", "Gamma"]
+            .map(|name| contents.find(&format!("object {} {{", name)).unwrap());
+        assert!(positions.is_sorted());
     }
 
-    // Generate the string representation
-    let mut contents = String::new();
+    #[rstest]
+    #[case::deep(&[("A::B", "f"), ("A::B::C", "g")], r#"object A {
+        object B {
+            external fun f()
 
-    // Add root members
-    for member in &root.members {
-        contents.push_str(&format!("{}\n", member));
-    }
+            object C {
+                external fun g()
+            }
+        }
+    }"#)]
+    #[case::deep_child_first(&[("A::B::C", "g"), ("A::B", "f")], r#"object A {
+        object B {
+            external fun f()
 
-    // Add child objects recursively
-    fn add_child_objects(obj: &KotlinObject, contents: &mut String, indent: usize) {
-        let mut child_contents = String::new();
-        let mut is_first = true;
-        for member in &obj.members {
-            let indent = "\n    ";
-            child_contents.push_str(&format!("{}{}", if is_first { "" } else { indent }, member));
-            is_first = false;
+            object C {
+                external fun g()
+            }
+        }
+    }"#)]
+    #[case::siblings(&[("A::B", "f"), ("A::C", "g"), ("A::B", "h")], r#"object A {
+        object B {
+            external fun f()
+            external fun h()
         }
 
-        for child in &obj.child_objects {
-            add_child_objects(child, &mut child_contents, indent + 1);
+        object C {
+            external fun g()
         }
+    }"#)]
+    #[case::every_level(&[("A::B::C", "h"), ("", "f"), ("A", "g"), ("A::D", "i"), ("A::B", "j")], r#"external fun f()
 
-        let child_str = KOTLIN_OBJECT_TEMPLATE
-            .replace("{name}", &obj.name)
-            .replace("{contents}", &child_contents);
+    object A {
+        external fun g()
 
-        // Replace each new line with the new line followed by the indent string
-        let indented_child_str = child_str.replace("\n", "\n    ");
+        object B {
+            external fun j()
 
-        contents.push_str(&indented_child_str);
-    }
+            object C {
+                external fun h()
+            }
+        }
 
-    for child in &root.child_objects {
-        add_child_objects(child, &mut contents, 0);
+        object D {
+            external fun i()
+        }
+    }"#)]
+    fn test_generate_kotlin_file_merges_objects(
+        #[case] functions: &[(&str, &str)],
+        #[case] expected: &str,
+    ) {
+        assert_eq!(
+            generate_kotlin_file(
+                "MyCrate",
+                "com.example",
+                "mylib",
+                &bindings(functions),
+                &Templates::default()
+            ),
+            format!(
+                r#"package com.example;
+
+object MyCrate {{
+    init {{
+        System.loadLibrary("mylib")
+    }}
+
+    {}
+}}
+"#,
+                expected
+            )
+        );
     }
 
-    // Return the complete Kotlin file
-    KOTLIN_ROOT_OBJECT_TEMPLATE
-        .replace("{package_name}", package_name)
-        .replace("{name}", &root.name)
-        .replace("{library_name}", library_name)
-        .replace("{contents}", &contents)
+    #[test]
+    fn test_generate_crate_kotlin_file() {
+        let config = crate::config::parse(
+            r#"[
+                {
+                    "name": "first-crate",
+                    "members": [
+                        {"type": "function", "name": "first_crate::add", "inputs": [], "output": "i32"}
+                    ]
+                },
+                {
+                    "name": "second-crate",
+                    "members": [
+                        {"type": "function", "name": "second_crate::geo::distance", "inputs": [], "output": "f64"}
+                    ]
+                }
+            ]"#,
+        )
+        .unwrap();
+        let files = config
+            .iter()
+            .map(|package| {
+                let functions = package
+                    .members
+                    .iter()
+                    .flat_map(|member| match member {
+                        Member::Function(function) => function.monomorphize().unwrap(),
+                        _ => Vec::new(),
+                    })
+                    .collect::<Vec<_>>();
+                generate_crate_kotlin_file(
+                    package,
+                    &functions,
+                    &HashMap::new(),
+                    "com.example",
+                    "mylib",
+                    &Templates::default(),
+                )
+                .unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(files[0].0, "FirstCrate");
+        assert!(files[0].1.contains("object FirstCrate {"));
+        assert!(files[0].1.contains("external fun add(): Int"));
+        assert!(!files[0].1.contains("distance"));
+
+        assert_eq!(files[1].0, "SecondCrate");
+        assert!(files[1].1.contains("object SecondCrate {"));
+        assert!(files[1].1.contains("external fun distance(): Double"));
+        assert!(!files[1].1.contains("add"));
+    }
 }
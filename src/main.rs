@@ -1,82 +1,465 @@
-use config::parse;
-use functions::{generate_jni_function, generate_kotlin_function};
-use kotlin::generate_kotlin_file;
-use names::to_pascal_case;
+use clap::{Args, Parser, Subcommand};
+use config::{Crate, Function, Member, parse, starter_config, upgrade};
+use docs::find_function_docs;
+use error::{Diagnostic, Error};
+use functions::{
+    find_invalid_defaults, find_invalid_kotlin_names, find_java_keyword_names,
+    find_kotlin_collisions, find_unsupported_types, generate_jni_function,
+};
+use kotlin::generate_crate_kotlin_file;
+use std::collections::HashMap;
 use std::fs::File;
-use std::io::Write;
-use std::{env, fs, path::Path};
+use std::io::{self, Write};
+use std::process::ExitCode;
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+use template::Templates;
+use types::SUPPORTED_TYPES;
 
 mod cargo;
 mod config;
-mod converters;
+mod docs;
+mod error;
 mod functions;
 mod kotlin;
 mod names;
+mod output;
+mod rust_type;
+mod template;
 mod types;
 
-fn main() -> std::io::Result<()> {
-    let args: Vec<String> = env::args().collect();
+/// Generates JNI bindings and Kotlin declarations for the functions of Rust crates.
+#[derive(Parser)]
+#[command(version)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Generate the bindings of a config
+    Generate(GenerateArgs),
+    /// Write a starter config
+    Init {
+        /// The crate to bind
+        #[arg(long = "crate", value_name = "NAME", default_value = "my-crate")]
+        crate_name: String,
+        /// The path of the config to write
+        #[arg(long, value_name = "PATH", default_value = "config.json")]
+        output: PathBuf,
+        /// Overwrite an existing config
+        #[arg(long)]
+        force: bool,
+    },
+    /// Check a config without generating anything
+    Validate {
+        /// The path of the config
+        config: PathBuf,
+    },
+    /// Check that the generated bindings are up to date with a config
+    Check(GenerateArgs),
+    /// Rewrite a config in an older format in the current one
+    Migrate {
+        /// The path of the config
+        config: PathBuf,
+    },
+    /// List the supported Rust types and the Kotlin types they are bound to
+    ListTypes,
+    /// Print the JSON Schema of the config, which editors can use to check configs
+    Schema,
+}
 
-    if args.len() != 4 {
-        eprintln!("Usage: {} <lib> <config> <java_package>", args[0]);
-        std::process::exit(1);
+#[derive(Args)]
+struct GenerateArgs {
+    /// The name of the cargo library to generate. This can be anything
+    lib: String,
+    /// The path of the config
+    config: PathBuf,
+    /// The Java package of the bindings
+    java_package: String,
+    /// A directory of templates that replace the built-in ones
+    #[arg(long, value_name = "DIR")]
+    templates: Option<PathBuf>,
+    /// The directory the cargo library is generated in, as `<DIR>/<LIB>`
+    #[arg(long, value_name = "DIR", default_value = "codegen")]
+    out_dir: PathBuf,
+    /// The directory of the cargo library, instead of `<OUT_DIR>/<LIB>`
+    #[arg(long, value_name = "DIR")]
+    rust_out: Option<PathBuf>,
+    /// The directory the Kotlin files are written to, instead of the directory of the cargo library
+    #[arg(long, value_name = "DIR")]
+    kotlin_out: Option<PathBuf>,
+    /// Skip tidying the generated Rust code with `cargo clippy --fix` and `cargo fmt`
+    #[arg(long)]
+    skip_format: bool,
+}
+
+impl GenerateArgs {
+    fn lib_path(&self) -> PathBuf {
+        self.rust_out
+            .clone()
+            .unwrap_or_else(|| self.out_dir.join(&self.lib))
     }
 
-    let lib_name = &args[1];
-    let config_path = &args[2];
-    let java_package = &args[3];
+    fn kotlin_path(&self) -> PathBuf {
+        self.kotlin_out.clone().unwrap_or_else(|| self.lib_path())
+    }
 
-    let codegen_path = "codegen";
+    /// The absolute directory of the config, which the paths of crates are relative to.
+    fn config_dir(&self) -> io::Result<PathBuf> {
+        let config = std::path::absolute(&self.config)?;
+        Ok(config.parent().map(Path::to_path_buf).unwrap_or(config))
+    }
+}
 
-    // Clean existing generated code
-    let lib_path = Path::new(codegen_path).join(lib_name);
-    if lib_path.exists() {
-        fs::remove_dir_all(&lib_path)?;
+/// Exits with 0 on success, 1 when the config is invalid, the bindings are out of date or
+/// generation fails, and 2 when the arguments are invalid.
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let result = match &cli.command {
+        Command::Generate(args) => {
+            generate(args, &args.lib_path(), &args.kotlin_path()).map(|()| ExitCode::SUCCESS)
+        }
+        Command::Init {
+            crate_name,
+            output,
+            force,
+        } => init(crate_name, output, *force).map(|()| ExitCode::SUCCESS),
+        Command::Validate { config } => validate(config).map(|()| ExitCode::SUCCESS),
+        Command::Check(args) => check(args),
+        Command::Migrate { config } => migrate(config).map(|()| ExitCode::SUCCESS),
+        Command::ListTypes => {
+            list_types();
+            Ok(ExitCode::SUCCESS)
+        }
+        Command::Schema => {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&config::schema()).unwrap()
+            );
+            Ok(ExitCode::SUCCESS)
+        }
+    };
+    result.unwrap_or_else(|e| {
+        match e {
+            Error::Config(diagnostics) => {
+                for diagnostic in diagnostics {
+                    eprintln!("{}", diagnostic);
+                }
+            }
+            e => eprintln!("error: {}", e),
+        }
+        ExitCode::FAILURE
+    })
+}
+
+fn read_config_file(path: &Path) -> io::Result<String> {
+    fs::read_to_string(path).map_err(|e| {
+        io::Error::new(
+            e.kind(),
+            format!("could not read `{}`: {}", path.display(), e),
+        )
+    })
+}
+
+fn in_file(diagnostics: Vec<Diagnostic>, path: &Path) -> Vec<Diagnostic> {
+    diagnostics.into_iter().map(|d| d.in_file(path)).collect()
+}
+
+/// Reads the config at `path`, and checks it, printing its warnings.
+fn read_config(path: &Path) -> Result<Vec<Crate>, Error> {
+    let json = read_config_file(path)?;
+    let config = parse(&json).map_err(|e| Error::Config(in_file(e, path)))?;
+
+    let (errors, warnings) = validate_config(&config)
+        .into_iter()
+        .partition::<Vec<_>, _>(Diagnostic::is_error);
+    for warning in in_file(warnings, path) {
+        eprintln!("{}", warning);
+    }
+    if !errors.is_empty() {
+        return Err(Error::Config(in_file(errors, path)));
+    }
+    Ok(config)
+}
+
+/// The functions of a crate, with a member for each instantiation of a generic function.
+fn get_functions(package: &Crate) -> Result<Vec<Function>, Vec<Diagnostic>> {
+    let mut functions = Vec::new();
+    let mut errors = Vec::new();
+    for member in &package.members {
+        if let Member::Function(function) = member {
+            match function.monomorphize() {
+                Ok(monomorphized) => functions.extend(monomorphized),
+                Err(e) => errors.push(
+                    Diagnostic::error(e.to_string())
+                        .in_crate(&package.name)
+                        .in_member(&function.name),
+                ),
+            }
+        }
     }
+    if errors.is_empty() {
+        Ok(functions)
+    } else {
+        Err(errors)
+    }
+}
+
+/// Finds the errors and warnings of a config.
+fn validate_config(config: &[Crate]) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
 
-    // Read config file
-    let config = parse(&fs::read_to_string(config_path)?).unwrap();
+    // Each crate has a Kotlin file of its own, named after its root object
+    let mut root_objects: Vec<(String, &str)> = Vec::new();
+    for package in config {
+        let naming = package.naming.clone().unwrap_or_default();
+        let root_object = naming.to_pascal_case(&package.name);
+        if let Some((_, other)) = root_objects.iter().find(|(o, _)| *o == root_object) {
+            diagnostics.push(Diagnostic::error(format!(
+                "`{}` and `{}` are both bound to the Kotlin object `{}`",
+                other, package.name, root_object
+            )));
+        }
+        root_objects.push((root_object, &package.name));
+    }
 
-    // Create cargo lib
-    fs::create_dir_all(codegen_path)?;
-    cargo::new_lib(lib_name, codegen_path)?;
+    for package in config {
+        let crate_diagnostics = package.validate();
+        let has_errors = crate_diagnostics.iter().any(Diagnostic::is_error);
+        diagnostics.extend(
+            crate_diagnostics
+                .into_iter()
+                .map(|d| d.in_crate(&package.name)),
+        );
+        // The functions of invalid members cannot be checked
+        if has_errors {
+            continue;
+        }
 
-    // Add dependencies
-    for package in config.iter() {
-        cargo::add(&package.name, lib_path.to_str().unwrap())?;
+        let naming = package.naming.clone().unwrap_or_default();
+        let functions = match get_functions(package) {
+            Ok(functions) => functions,
+            Err(errors) => {
+                diagnostics.extend(errors);
+                continue;
+            }
+        };
+
+        let mut crate_diagnostics = find_java_keyword_names(&functions, &naming);
+        crate_diagnostics.extend(find_unsupported_types(&functions));
+        crate_diagnostics.extend(find_invalid_kotlin_names(&functions, &naming));
+        crate_diagnostics.extend(find_invalid_defaults(&functions));
+        crate_diagnostics.extend(find_kotlin_collisions(&functions, &naming));
+        diagnostics.extend(
+            crate_diagnostics
+                .into_iter()
+                .map(|d| d.in_crate(&package.name)),
+        );
     }
+    diagnostics
+}
+
+fn validate(config_path: &Path) -> Result<(), Error> {
+    read_config(config_path)?;
+    println!("`{}` is valid", config_path.display());
+    Ok(())
+}
 
-    cargo::add("jni", lib_path.to_str().unwrap())?;
+fn migrate(config_path: &Path) -> Result<(), Error> {
+    let json = read_config_file(config_path)?;
+    let migrated = upgrade(&json).map_err(|e| Error::Config(in_file(e, config_path)))?;
+    if migrated == json {
+        println!("`{}` is already up to date", config_path.display());
+        return Ok(());
+    }
+    fs::write(config_path, migrated)?;
+    println!(
+        "Migrated `{}` to version {} of the config format",
+        config_path.display(),
+        config::CONFIG_VERSION
+    );
+    Ok(())
+}
+
+fn init(crate_name: &str, output: &Path, force: bool) -> Result<(), Error> {
+    if output.exists() && !force {
+        return Err(Error::Io(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!(
+                "`{}` already exists; use --force to overwrite it",
+                output.display()
+            ),
+        )));
+    }
+    fs::write(output, starter_config(crate_name))?;
+    println!("Wrote a starter config to `{}`", output.display());
+    Ok(())
+}
+
+fn list_types() {
+    let width = SUPPORTED_TYPES
+        .iter()
+        .map(|(rust, _)| rust.len())
+        .max()
+        .unwrap_or_default();
+    for (rust, kotlin) in SUPPORTED_TYPES {
+        println!("{:width$}  {}", rust, kotlin, width = width);
+    }
+}
+
+/// Generates the bindings into a temporary directory, and compares them with the generated
+/// `Cargo.toml`, Rust source and Kotlin files.
+fn check(args: &GenerateArgs) -> Result<ExitCode, Error> {
+    let directory = env::temp_dir().join(format!(
+        "jni-binding-generator-check-{}",
+        std::process::id()
+    ));
+    if directory.exists() {
+        fs::remove_dir_all(&directory)?;
+    }
+    let lib_path = directory.join(&args.lib);
+    let kotlin_path = directory.join("kotlin");
+    let result = generate(args, &lib_path, &kotlin_path).and_then(|()| {
+        let files = pair_generated_files(
+            (&lib_path, &kotlin_path),
+            (&args.lib_path(), &args.kotlin_path()),
+        )?;
+
+        let mut up_to_date = true;
+        for (generated, existing) in files {
+            if fs::read(&existing).ok() != Some(fs::read(&generated)?) {
+                eprintln!("`{}` is out of date", existing.display());
+                up_to_date = false;
+            }
+        }
+        Ok(up_to_date)
+    });
+    if directory.exists() {
+        fs::remove_dir_all(&directory)?;
+    }
+
+    if !result? {
+        eprintln!("Run `generate` to update the bindings");
+        return Ok(ExitCode::FAILURE);
+    }
+    println!("The bindings are up to date");
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Pairs each file generated in the `generated` lib and Kotlin directories with the file at the
+/// same place in the `existing` ones.
+fn pair_generated_files(
+    (lib_path, kotlin_path): (&Path, &Path),
+    (existing_lib_path, existing_kotlin_path): (&Path, &Path),
+) -> io::Result<Vec<(PathBuf, PathBuf)>> {
+    let mut files = ["Cargo.toml", "src/lib.rs"]
+        .iter()
+        .map(|file| (lib_path.join(file), existing_lib_path.join(file)))
+        .collect::<Vec<_>>();
+    let mut kotlin_files = Vec::new();
+    for entry in fs::read_dir(kotlin_path)? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|e| e == "kt") {
+            kotlin_files.push(path);
+        }
+    }
+    kotlin_files.sort();
+    for path in kotlin_files {
+        let existing = existing_kotlin_path.join(path.file_name().unwrap());
+        files.push((path, existing));
+    }
+    Ok(files)
+}
+
+fn generate(args: &GenerateArgs, lib_path: &Path, kotlin_path: &Path) -> Result<(), Error> {
+    let templates = Templates::load(args.templates.as_deref())?;
+    let config = read_config(&args.config)?;
+
+    // Clean existing generated code
+    output::remove_generated_dir(lib_path)?;
+
+    let lib_name = &args.lib;
+    let java_package = &args.java_package;
+
+    // Create cargo lib, marked first so that the next run can replace it if this one fails
+    fs::create_dir_all(lib_path)?;
+    output::mark_generated_dir(lib_path)?;
+    cargo::new_lib(lib_name, lib_path, &config, &args.config_dir()?)?;
+    fs::create_dir_all(kotlin_path)?;
 
     // Generate lib.rs
     let mut imports = vec![
-        "jni::objects::{JClass, JString, JObject}".to_string(),
-        "jni::sys::{jfloat, jstring, jdouble, jint, jlong, jbyte, jshort, jchar, jboolean}"
+        "jni::objects::{JClass, JString, JObject, JObjectArray, JMap, JValue}".to_string(),
+        "jni::objects::{JBooleanArray, JByteArray, JCharArray, JShortArray, JIntArray, JLongArray, JFloatArray, JDoubleArray}".to_string(),
+        "jni::sys::{jfloat, jstring, jobject, jdouble, jint, jlong, jbyte, jshort, jchar, jboolean, jsize}"
             .to_string(),
+        "jni::sys::{jobjectArray, jbooleanArray, jbyteArray, jcharArray, jshortArray, jintArray, jlongArray, jfloatArray, jdoubleArray}".to_string(),
         "jni::JNIEnv".to_string(),
     ];
 
     let mut bindings = Vec::new();
-    let mut kotlin_bindings = Vec::new();
+    let mut errors = Vec::new();
+
+    // The sources are only read for docs, so the bindings are generated without them on failure
+    let sources = cargo::find_lib_sources(lib_path).unwrap_or_else(|e| {
+        eprintln!(
+            "warning: could not find the sources of the crates, so their docs are left out: {}",
+            e
+        );
+        HashMap::new()
+    });
 
     for package in config.iter() {
-        for member in &package.members {
-            imports.push(member.name.clone());
-            if member.member_type == "function" {
-                bindings.push(generate_jni_function(java_package, member));
-                kotlin_bindings.push(generate_kotlin_function(member));
+        let naming = package.naming.clone().unwrap_or_default();
+        let wrappers = package.kotlin_wrappers.unwrap_or_default();
+        let functions = get_functions(package).map_err(Error::Config)?;
+
+        let docs = match sources.get(&package.name) {
+            Some(source) => find_function_docs(&package.name.replace('-', "_"), source)
+                .unwrap_or_else(|e| {
+                    eprintln!(
+                        "warning: could not read the docs of `{}`: {}",
+                        package.name, e
+                    );
+                    HashMap::new()
+                }),
+            None => HashMap::new(),
+        };
+
+        // A type the checks of the config let through is reported instead of generating bad code
+        for function in &functions {
+            match generate_jni_function(java_package, function, &naming, wrappers, &templates) {
+                Ok(binding) => bindings.push(binding),
+                Err(reason) => errors.push(
+                    Diagnostic::error(format!("cannot generate the Rust function: {}", reason))
+                        .in_crate(&package.name)
+                        .in_member(&function.name),
+                ),
             }
         }
 
-        let kotlin_object_name = to_pascal_case(&package.name);
-        let kotlin_contents = generate_kotlin_file(
-            &kotlin_object_name,
+        match generate_crate_kotlin_file(
+            package,
+            &functions,
+            &docs,
             java_package,
             lib_name,
-            &kotlin_bindings,
-        );
-        let mut kotlin_file = File::create(lib_path.join(format!("{}.kt", kotlin_object_name)))?;
-        kotlin_file.write_all(kotlin_contents.as_bytes())?;
+            &templates,
+        ) {
+            Ok((kotlin_object_name, kotlin_contents)) => {
+                let mut kotlin_file =
+                    File::create(kotlin_path.join(format!("{}.kt", kotlin_object_name)))?;
+                kotlin_file.write_all(kotlin_contents.as_bytes())?;
+            }
+            Err(kotlin_errors) => errors.extend(kotlin_errors),
+        }
+    }
+    if !errors.is_empty() {
+        return Err(Error::Config(in_file(errors, &args.config)));
     }
 
     imports.sort();
@@ -96,8 +479,96 @@ fn main() -> std::io::Result<()> {
     file.write_all(contents.as_bytes())?;
 
     // Format code
-    cargo::clippy_fix(lib_path.to_str().unwrap())?;
-    cargo::format(lib_path.to_str().unwrap())?;
+    if !args.skip_format {
+        cargo::clippy_fix(lib_path)?;
+        cargo::format(lib_path)?;
+    }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+    use rstest::rstest;
+
+    #[test]
+    fn test_cli() {
+        Cli::command().debug_assert();
+    }
+
+    #[rstest]
+    #[case::generate(&["generate", "mylib", "config.json", "com.example"], true)]
+    #[case::generate_options(&["generate", "--out-dir", "out", "--kotlin-out", "kt", "mylib", "config.json", "com.example"], true)]
+    #[case::generate_missing_package(&["generate", "mylib", "config.json"], false)]
+    #[case::init(&["init", "--crate", "my-crate"], true)]
+    #[case::validate(&["validate", "config.json"], true)]
+    #[case::check(&["check", "mylib", "config.json", "com.example"], true)]
+    #[case::list_types(&["list-types"], true)]
+    #[case::migrate(&["migrate", "config.json"], true)]
+    #[case::schema(&["schema"], true)]
+    #[case::positional(&["mylib", "config.json", "com.example"], false)]
+    fn test_parse_arguments(#[case] args: &[&str], #[case] valid: bool) {
+        let args = std::iter::once("jni-binding-generator").chain(args.iter().copied());
+        assert_eq!(Cli::try_parse_from(args).is_ok(), valid);
+    }
+
+    #[rstest]
+    #[case::bare_file_name("config.json", "")]
+    #[case::current_dir("./config.json", "")]
+    #[case::subdirectory("configs/config.json", "configs")]
+    fn test_config_dir(#[case] config: &str, #[case] expected: &str) {
+        let cli = Cli::try_parse_from([
+            "jni-binding-generator",
+            "generate",
+            "mylib",
+            config,
+            "com.example",
+        ])
+        .unwrap();
+        let Command::Generate(args) = cli.command else {
+            panic!("Expected the generate command");
+        };
+        let current_dir = env::current_dir().unwrap();
+        assert_eq!(
+            args.config_dir().unwrap(),
+            std::path::absolute(current_dir.join(expected)).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_pair_generated_files() {
+        let directory = env::temp_dir().join(format!("check-{}", std::process::id()));
+        let lib_path = directory.join("generated/mylib");
+        fs::create_dir_all(lib_path.join("src")).unwrap();
+        fs::write(lib_path.join("MyCrate.kt"), "").unwrap();
+        fs::write(lib_path.join("Cargo.toml"), "").unwrap();
+
+        let existing_lib_path = directory.join("existing/mylib");
+        let existing_kotlin_path = directory.join("existing/kotlin");
+        assert_eq!(
+            pair_generated_files(
+                (&lib_path, &lib_path),
+                (&existing_lib_path, &existing_kotlin_path)
+            )
+            .unwrap(),
+            vec![
+                (
+                    lib_path.join("Cargo.toml"),
+                    existing_lib_path.join("Cargo.toml")
+                ),
+                (
+                    lib_path.join("src/lib.rs"),
+                    existing_lib_path.join("src/lib.rs")
+                ),
+                (
+                    lib_path.join("MyCrate.kt"),
+                    existing_kotlin_path.join("MyCrate.kt")
+                ),
+            ]
+        );
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -1,7 +1,11 @@
-pub fn to_pascal_case(name: &str) -> String {
+/// Converts to pascal case, keeping words that are in `acronyms` upper case.
+pub fn to_pascal_case(name: &str, acronyms: &[String]) -> String {
     name.replace(['-', '_'], " ")
         .split_whitespace()
         .map(|word| {
+            if acronyms.iter().any(|a| a.eq_ignore_ascii_case(word)) {
+                return word.to_uppercase();
+            }
             let mut chars = word.chars();
             match chars.next() {
                 None => String::new(),
@@ -14,12 +18,15 @@ pub fn to_pascal_case(name: &str) -> String {
         .collect::<String>()
 }
 
-pub fn to_camel_case(name: &str) -> String {
-    let pascal = to_pascal_case(name);
-    let mut chars = pascal.chars();
-    match chars.next() {
+/// Converts to camel case, keeping words other than the first that are in `acronyms` upper case.
+pub fn to_camel_case(name: &str, acronyms: &[String]) -> String {
+    let name = name.replace(['-', '_'], " ");
+    let mut words = name.split_whitespace();
+    match words.next() {
         None => String::new(),
-        Some(first) => first.to_lowercase().chain(chars).collect(),
+        Some(first) => {
+            first.to_lowercase() + &to_pascal_case(&words.collect::<Vec<_>>().join(" "), acronyms)
+        }
     }
 }
 
@@ -37,6 +44,147 @@ pub fn get_unqualified_name(full_path: &str) -> String {
     full_path.split("::").last().unwrap().to_string()
 }
 
+/// Escapes a name for use in a JNI native method symbol.
+pub fn escape_jni_name(name: &str) -> String {
+    name.replace('_', "_1")
+        .replace(';', "_2")
+        .replace('[', "_3")
+        .replace('$', "_00024")
+        .replace(['.', '/'], "_")
+}
+
+/// Kotlin keywords that cannot be used as identifiers without backticks.
+const KOTLIN_HARD_KEYWORDS: &[&str] = &[
+    "as",
+    "break",
+    "class",
+    "continue",
+    "do",
+    "else",
+    "false",
+    "for",
+    "fun",
+    "if",
+    "in",
+    "interface",
+    "is",
+    "null",
+    "object",
+    "package",
+    "return",
+    "super",
+    "this",
+    "throw",
+    "true",
+    "try",
+    "typealias",
+    "typeof",
+    "val",
+    "var",
+    "when",
+    "while",
+];
+
+/// Java keywords and literals, which Java callers cannot use as method or class names.
+const JAVA_KEYWORDS: &[&str] = &[
+    "_",
+    "abstract",
+    "assert",
+    "boolean",
+    "break",
+    "byte",
+    "case",
+    "catch",
+    "char",
+    "class",
+    "const",
+    "continue",
+    "default",
+    "do",
+    "double",
+    "else",
+    "enum",
+    "extends",
+    "false",
+    "final",
+    "finally",
+    "float",
+    "for",
+    "goto",
+    "if",
+    "implements",
+    "import",
+    "instanceof",
+    "int",
+    "interface",
+    "long",
+    "native",
+    "new",
+    "null",
+    "package",
+    "private",
+    "protected",
+    "public",
+    "return",
+    "short",
+    "static",
+    "strictfp",
+    "super",
+    "switch",
+    "synchronized",
+    "this",
+    "throw",
+    "throws",
+    "transient",
+    "true",
+    "try",
+    "void",
+    "volatile",
+    "while",
+];
+
+/// Characters that are not allowed in names on the JVM, even between backticks.
+const INVALID_JVM_NAME_CHARACTERS: &[char] = &[
+    '.', ';', '[', ']', '/', '<', '>', ':', '\\', '`', '\n', '\r',
+];
+
+/// Wraps a name in backticks if it is a Kotlin keyword.
+pub fn escape_kotlin_name(name: &str) -> String {
+    if KOTLIN_HARD_KEYWORDS.contains(&name) {
+        format!("`{}`", name)
+    } else {
+        name.to_string()
+    }
+}
+
+pub fn is_java_keyword(name: &str) -> bool {
+    JAVA_KEYWORDS.contains(&name)
+}
+
+/// Checks that a name can be used on the JVM, returning the reason when it cannot.
+pub fn check_jvm_name(name: &str) -> Result<(), String> {
+    if name.is_empty() {
+        return Err("names cannot be empty".to_string());
+    }
+    match name
+        .chars()
+        .find(|c| INVALID_JVM_NAME_CHARACTERS.contains(c))
+    {
+        Some(c) => Err(format!("names cannot contain {:?}", c)),
+        None => Ok(()),
+    }
+}
+
+/// Whether a name is a Rust identifier that can name a parameter, which excludes keywords.
+pub fn is_rust_identifier(name: &str) -> bool {
+    syn::parse_str::<syn::Ident>(name).is_ok()
+}
+
+/// Removes the `r#` of a raw identifier, such as `r#type`, which is not part of its name.
+pub fn strip_raw_prefix(name: &str) -> &str {
+    name.strip_prefix("r#").unwrap_or(name)
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -48,7 +196,7 @@ mod test {
     #[case("simple", "Simple")]
     #[case("ALREADY_UPPER", "AlreadyUpper")]
     fn test_to_pascal_case(#[case] input: &str, #[case] expected: &str) {
-        assert_eq!(to_pascal_case(input), expected);
+        assert_eq!(to_pascal_case(input, &[]), expected);
     }
 
     #[rstest]
@@ -57,7 +205,25 @@ mod test {
     #[case("simple", "simple")]
     #[case("ALREADY_UPPER", "alreadyUpper")]
     fn test_to_camel_case(#[case] input: &str, #[case] expected: &str) {
-        assert_eq!(to_camel_case(input), expected);
+        assert_eq!(to_camel_case(input, &[]), expected);
+    }
+
+    #[rstest]
+    #[case("parse_gps_nmea", "ParseGPSNMEA")]
+    #[case("gps", "GPS")]
+    #[case("gpsd", "Gpsd")]
+    fn test_to_pascal_case_acronyms(#[case] input: &str, #[case] expected: &str) {
+        let acronyms = vec!["GPS".to_string(), "nmea".to_string()];
+        assert_eq!(to_pascal_case(input, &acronyms), expected);
+    }
+
+    #[rstest]
+    #[case("parse_gps_nmea", "parseGPSNMEA")]
+    #[case("gps_fix", "gpsFix")]
+    #[case("ALREADY_UPPER", "alreadyUpper")]
+    fn test_to_camel_case_acronyms(#[case] input: &str, #[case] expected: &str) {
+        let acronyms = vec!["GPS".to_string(), "nmea".to_string()];
+        assert_eq!(to_camel_case(input, &acronyms), expected);
     }
 
     #[rstest]
@@ -75,4 +241,61 @@ mod test {
     fn test_get_unqualified_name(#[case] input: &str, #[case] expected: &str) {
         assert_eq!(get_unqualified_name(input), expected);
     }
+
+    #[rstest]
+    #[case("com.example_test", "com_example_1test")]
+    #[case("[F", "_3F")]
+    #[case("Ljava/lang/String;", "Ljava_lang_String_2")]
+    #[case("Outer$Inner", "Outer_00024Inner")]
+    fn test_escape_jni_name(#[case] input: &str, #[case] expected: &str) {
+        assert_eq!(escape_jni_name(input), expected);
+    }
+
+    #[rstest]
+    #[case("fun", "`fun`")]
+    #[case("object", "`object`")]
+    #[case("when", "`when`")]
+    #[case("value", "value")]
+    #[case("native", "native")]
+    fn test_escape_kotlin_name(#[case] input: &str, #[case] expected: &str) {
+        assert_eq!(escape_kotlin_name(input), expected);
+    }
+
+    #[rstest]
+    #[case("native", true)]
+    #[case("int", true)]
+    #[case("fun", false)]
+    fn test_is_java_keyword(#[case] input: &str, #[case] expected: bool) {
+        assert_eq!(is_java_keyword(input), expected);
+    }
+
+    #[rstest]
+    #[case("parseNmea", Ok(()))]
+    #[case("fun", Ok(()))]
+    #[case("", Err("names cannot be empty".to_string()))]
+    #[case("parse.nmea", Err("names cannot contain '.'".to_string()))]
+    #[case("List<Int>", Err("names cannot contain '<'".to_string()))]
+    fn test_check_jvm_name(#[case] input: &str, #[case] expected: Result<(), String>) {
+        assert_eq!(check_jvm_name(input), expected);
+    }
+
+    #[rstest]
+    #[case("value", true)]
+    #[case("_value2", true)]
+    #[case("r#type", true)]
+    #[case("type", false)]
+    #[case("self", false)]
+    #[case("2d", false)]
+    #[case("first name", false)]
+    #[case("", false)]
+    fn test_is_rust_identifier(#[case] name: &str, #[case] expected: bool) {
+        assert_eq!(is_rust_identifier(name), expected);
+    }
+
+    #[rstest]
+    #[case("r#type", "type")]
+    #[case("value", "value")]
+    fn test_strip_raw_prefix(#[case] name: &str, #[case] expected: &str) {
+        assert_eq!(strip_raw_prefix(name), expected);
+    }
 }
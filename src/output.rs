@@ -0,0 +1,65 @@
+use std::{
+    fs,
+    io::{Error, Result},
+    path::Path,
+};
+
+/// The file marking a directory as created by the generator, which may then be replaced.
+pub const MARKER_FILE: &str = ".jni-binding-generator";
+
+/// Deletes the generated directory at `path` so it can be generated again. Directories that were
+/// not created by the generator are left untouched, and reported as an error unless empty.
+pub fn remove_generated_dir(path: &Path) -> Result<()> {
+    if !path.exists() {
+        return Ok(());
+    }
+    if path.join(MARKER_FILE).is_file() {
+        return fs::remove_dir_all(path);
+    }
+    if path.is_dir() && fs::read_dir(path)?.next().is_none() {
+        return fs::remove_dir(path);
+    }
+    Err(Error::other(format!(
+        "refusing to delete `{}`, which was not created by the generator (it has no `{}` file); delete it yourself or generate into another directory",
+        path.display(),
+        MARKER_FILE
+    )))
+}
+
+/// Marks the directory at `path` as created by the generator.
+pub fn mark_generated_dir(path: &Path) -> Result<()> {
+    fs::write(
+        path.join(MARKER_FILE),
+        "This directory was created by jni-binding-generator and is replaced each time it runs.\n",
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_remove_generated_dir() {
+        let directory = std::env::temp_dir().join(format!("output-{}", std::process::id()));
+        let generated = directory.join("generated");
+        let edited = directory.join("edited");
+        let empty = directory.join("empty");
+        fs::create_dir_all(generated.join("src")).unwrap();
+        fs::create_dir_all(&edited).unwrap();
+        fs::create_dir_all(&empty).unwrap();
+        fs::write(generated.join("src/lib.rs"), "").unwrap();
+        mark_generated_dir(&generated).unwrap();
+        fs::write(edited.join("lib.rs"), "// Local edits").unwrap();
+
+        remove_generated_dir(&generated).unwrap();
+        assert!(!generated.exists());
+        remove_generated_dir(&empty).unwrap();
+        assert!(!empty.exists());
+        remove_generated_dir(&directory.join("missing")).unwrap();
+
+        assert!(remove_generated_dir(&edited).is_err());
+        assert!(edited.join("lib.rs").exists());
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
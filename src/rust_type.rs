@@ -0,0 +1,375 @@
+use std::{borrow::Cow, collections::HashMap, fmt};
+
+use schemars::{JsonSchema, Schema, SchemaGenerator, json_schema};
+use serde::Deserialize;
+use syn::{GenericArgument, PathArguments, Type};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MapKind {
+    Hash,
+    BTree,
+}
+
+/// A Rust type, parsed from the `type` of an input or the `output` of a member in the config.
+///
+/// Paths to standard library types are normalized, so `std::vec::Vec<f32>` and `Vec<f32>` are
+/// the same type, and lifetimes are discarded.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum RustType {
+    Named(String),
+    Reference {
+        mutable: bool,
+        inner: Box<RustType>,
+    },
+    Slice(Box<RustType>),
+    Vec(Box<RustType>),
+    Map(MapKind, Box<RustType>, Box<RustType>),
+    /// A `Result` output, whose `Ok` value is returned and whose `Err` value is thrown. The `Ok`
+    /// type is `None` for the unit type.
+    Result(Option<Box<RustType>>, Box<RustType>),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct TypeError {
+    pub rust_type: String,
+    pub reason: String,
+}
+
+impl fmt::Display for TypeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Unsupported type `{}`: {}", self.rust_type, self.reason)
+    }
+}
+
+impl std::error::Error for TypeError {}
+
+impl TryFrom<String> for RustType {
+    type Error = TypeError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        parse_type(&value)
+    }
+}
+
+/// Types are written as strings in the config.
+impl JsonSchema for RustType {
+    fn schema_name() -> Cow<'static, str> {
+        "RustType".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "type": "string",
+            "description": "A Rust type, such as `i32`, `&str`, `&[f32]` or `HashMap<String, i64>`.",
+        })
+    }
+}
+
+impl fmt::Display for RustType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RustType::Named(name) => write!(f, "{}", name),
+            RustType::Reference { mutable, inner } => {
+                write!(f, "&{}{}", if *mutable { "mut " } else { "" }, inner)
+            }
+            RustType::Slice(element) => write!(f, "[{}]", element),
+            RustType::Vec(element) => write!(f, "Vec<{}>", element),
+            RustType::Map(MapKind::Hash, key, value) => write!(f, "HashMap<{}, {}>", key, value),
+            RustType::Map(MapKind::BTree, key, value) => {
+                write!(f, "BTreeMap<{}, {}>", key, value)
+            }
+            RustType::Result(Some(ok), err) => write!(f, "Result<{}, {}>", ok, err),
+            RustType::Result(None, err) => write!(f, "Result<(), {}>", err),
+        }
+    }
+}
+
+impl RustType {
+    /// Replaces generic parameters with the types bound to them.
+    pub fn substitute(&self, bindings: &HashMap<String, RustType>) -> RustType {
+        match self {
+            RustType::Named(name) => bindings.get(name).cloned().unwrap_or_else(|| self.clone()),
+            RustType::Reference { mutable, inner } => RustType::Reference {
+                mutable: *mutable,
+                inner: Box::new(inner.substitute(bindings)),
+            },
+            RustType::Slice(element) => RustType::Slice(Box::new(element.substitute(bindings))),
+            RustType::Vec(element) => RustType::Vec(Box::new(element.substitute(bindings))),
+            RustType::Map(kind, key, value) => RustType::Map(
+                *kind,
+                Box::new(key.substitute(bindings)),
+                Box::new(value.substitute(bindings)),
+            ),
+            RustType::Result(ok, err) => RustType::Result(
+                ok.as_ref().map(|ok| Box::new(ok.substitute(bindings))),
+                Box::new(err.substitute(bindings)),
+            ),
+        }
+    }
+
+    /// The owned type used on the Java side, such as `String` for `&str` or `Vec<T>` for `&[T]`.
+    pub fn to_owned_type(&self) -> RustType {
+        match self {
+            RustType::Reference { inner, .. } => inner.to_owned_type(),
+            RustType::Slice(element) => RustType::Vec(element.clone()),
+            RustType::Named(name) if name == "str" => RustType::Named("String".to_string()),
+            _ => self.clone(),
+        }
+    }
+}
+
+pub fn parse_type(rust_type: &str) -> Result<RustType, TypeError> {
+    let parsed = syn::parse_str::<Type>(rust_type).map_err(|e| TypeError {
+        rust_type: rust_type.to_string(),
+        reason: e.to_string(),
+    })?;
+    let parsed = convert_type(&parsed, rust_type)?;
+
+    match &parsed {
+        RustType::Named(name) if name == "str" => {
+            Err(error(rust_type, "`str` must be behind a reference"))
+        }
+        RustType::Slice(_) => Err(error(rust_type, "slices must be behind a reference")),
+        _ => Ok(parsed),
+    }
+}
+
+fn error(rust_type: &str, reason: &str) -> TypeError {
+    TypeError {
+        rust_type: rust_type.to_string(),
+        reason: reason.to_string(),
+    }
+}
+
+fn convert_type(parsed: &Type, rust_type: &str) -> Result<RustType, TypeError> {
+    match parsed {
+        Type::Path(path) => {
+            if path.qself.is_some() {
+                return Err(error(rust_type, "qualified paths are not supported"));
+            }
+            convert_path(&path.path, rust_type)
+        }
+        Type::Reference(reference) => {
+            let inner = convert_type(&reference.elem, rust_type)?;
+            if matches!(inner, RustType::Reference { .. }) {
+                return Err(error(rust_type, "nested references are not supported"));
+            }
+            Ok(RustType::Reference {
+                mutable: reference.mutability.is_some(),
+                inner: Box::new(inner),
+            })
+        }
+        Type::Slice(slice) => Ok(RustType::Slice(Box::new(convert_element(
+            &slice.elem,
+            rust_type,
+        )?))),
+        Type::Paren(paren) => convert_type(&paren.elem, rust_type),
+        Type::Group(group) => convert_type(&group.elem, rust_type),
+        Type::Tuple(tuple) if tuple.elems.is_empty() => Err(error(
+            rust_type,
+            "the unit type is not supported, omit the output instead",
+        )),
+        Type::Tuple(_) => Err(error(rust_type, "tuples are not supported")),
+        Type::Array(_) => Err(error(
+            rust_type,
+            "fixed size arrays are not supported, use a Vec or slice instead",
+        )),
+        Type::Ptr(_) => Err(error(rust_type, "raw pointers are not supported")),
+        Type::BareFn(_) => Err(error(rust_type, "function pointers are not supported")),
+        Type::ImplTrait(_) | Type::TraitObject(_) => {
+            Err(error(rust_type, "trait types are not supported"))
+        }
+        Type::Never(_) => Err(error(rust_type, "the never type is not supported")),
+        Type::Infer(_) => Err(error(rust_type, "the type must be written out in full")),
+        _ => Err(error(rust_type, "this kind of type is not supported")),
+    }
+}
+
+/// Converts a generic argument or slice element, which must be an owned type.
+fn convert_element(parsed: &Type, rust_type: &str) -> Result<RustType, TypeError> {
+    let element = convert_type(parsed, rust_type)?;
+    match element {
+        RustType::Reference { .. } => Err(error(
+            rust_type,
+            "references inside collections are not supported",
+        )),
+        RustType::Slice(_) => Err(error(rust_type, "slices must be behind a reference")),
+        RustType::Named(ref name) if name == "str" => {
+            Err(error(rust_type, "`str` must be behind a reference"))
+        }
+        _ => Ok(element),
+    }
+}
+
+fn convert_path(path: &syn::Path, rust_type: &str) -> Result<RustType, TypeError> {
+    let segments = path
+        .segments
+        .iter()
+        .map(|s| s.ident.to_string())
+        .collect::<Vec<_>>();
+    let last = path.segments.last().unwrap();
+    let modules = segments[..segments.len() - 1]
+        .iter()
+        .map(|s| s.as_str())
+        .collect::<Vec<_>>();
+
+    let mut arguments = Vec::new();
+    match &last.arguments {
+        PathArguments::None => {}
+        PathArguments::AngleBracketed(generics) => {
+            for argument in &generics.args {
+                match argument {
+                    GenericArgument::Lifetime(_) => {}
+                    GenericArgument::Type(argument) => arguments.push(argument),
+                    _ => {
+                        return Err(error(
+                            rust_type,
+                            "only type and lifetime generic arguments are supported",
+                        ));
+                    }
+                }
+            }
+        }
+        PathArguments::Parenthesized(_) => {
+            return Err(error(rust_type, "function traits are not supported"));
+        }
+    }
+
+    let name = segments.last().unwrap().as_str();
+    let is_std = |expected: &[&[&str]]| modules.is_empty() || expected.contains(&&modules[..]);
+
+    let element = |index: usize| convert_element(arguments[index], rust_type).map(Box::new);
+
+    match (name, arguments.len()) {
+        ("Vec", 1) if is_std(&[&["std", "vec"], &["alloc", "vec"]]) => {
+            Ok(RustType::Vec(element(0)?))
+        }
+        ("HashMap", 2)
+            if is_std(&[&["std", "collections"], &["std", "collections", "hash_map"]]) =>
+        {
+            Ok(RustType::Map(MapKind::Hash, element(0)?, element(1)?))
+        }
+        ("BTreeMap", 2)
+            if is_std(&[
+                &["std", "collections"],
+                &["std", "collections", "btree_map"],
+                &["alloc", "collections"],
+                &["alloc", "collections", "btree_map"],
+            ]) =>
+        {
+            Ok(RustType::Map(MapKind::BTree, element(0)?, element(1)?))
+        }
+        // The error is only formatted, so it can be borrowed, as in `Result<T, &'static str>`
+        ("Result", 2) if is_std(&[&["std", "result"], &["core", "result"]]) => {
+            let ok = match arguments[0] {
+                Type::Tuple(tuple) if tuple.elems.is_empty() => None,
+                _ => Some(element(0)?),
+            };
+            let err = convert_type(arguments[1], rust_type)?;
+            Ok(RustType::Result(ok, Box::new(err)))
+        }
+        ("String", 0) if is_std(&[&["std", "string"], &["alloc", "string"]]) => {
+            Ok(RustType::Named(name.to_string()))
+        }
+        (_, 0) if is_std(&[&["std", "primitive"], &["core", "primitive"]]) => {
+            Ok(RustType::Named(name.to_string()))
+        }
+        (_, 0) => Ok(RustType::Named(segments.join("::"))),
+        _ => Err(error(
+            rust_type,
+            "generic types other than Vec, HashMap, BTreeMap and Result are not supported",
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("f32", "f32")]
+    #[case("std::primitive::f32", "f32")]
+    #[case("String", "String")]
+    #[case("std::string::String", "String")]
+    #[case("&str", "&str")]
+    #[case("& str", "&str")]
+    #[case("&'static str", "&str")]
+    #[case("&[f32]", "&[f32]")]
+    #[case("&mut [f32]", "&mut [f32]")]
+    #[case("&'a mut f32", "&mut f32")]
+    #[case("Vec<f32>", "Vec<f32>")]
+    #[case("std::vec::Vec<f32>", "Vec<f32>")]
+    #[case("Vec < Vec<f32> >", "Vec<Vec<f32>>")]
+    #[case("&Vec<String>", "&Vec<String>")]
+    #[case("std::collections::HashMap<String, f64>", "HashMap<String, f64>")]
+    #[case("BTreeMap<String,i32>", "BTreeMap<String, i32>")]
+    #[case("my_crate::Point", "my_crate::Point")]
+    #[case("Result<f32, my_crate::Error>", "Result<f32, my_crate::Error>")]
+    #[case("std::result::Result<(), &'static str>", "Result<(), &str>")]
+    fn test_parse_type(#[case] input: &str, #[case] expected: &str) {
+        assert_eq!(parse_type(input).unwrap().to_string(), expected);
+    }
+
+    #[rstest]
+    #[case("&str", "String")]
+    #[case("&[f32]", "Vec<f32>")]
+    #[case("&Vec<f32>", "Vec<f32>")]
+    #[case("f32", "f32")]
+    fn test_to_owned_type(#[case] input: &str, #[case] expected: &str) {
+        assert_eq!(
+            parse_type(input).unwrap().to_owned_type().to_string(),
+            expected
+        );
+    }
+
+    #[rstest]
+    #[case("(f32, f32)", "tuples are not supported")]
+    #[case("()", "the unit type is not supported, omit the output instead")]
+    #[case(
+        "[f32; 3]",
+        "fixed size arrays are not supported, use a Vec or slice instead"
+    )]
+    #[case("str", "`str` must be behind a reference")]
+    #[case("[f32]", "slices must be behind a reference")]
+    #[case("Vec<&str>", "references inside collections are not supported")]
+    #[case("&&str", "nested references are not supported")]
+    #[case("*const f32", "raw pointers are not supported")]
+    #[case("fn(f32) -> f32", "function pointers are not supported")]
+    #[case("impl Fn()", "trait types are not supported")]
+    #[case(
+        "Option<f32>",
+        "generic types other than Vec, HashMap, BTreeMap and Result are not supported"
+    )]
+    fn test_parse_type_unsupported(#[case] input: &str, #[case] reason: &str) {
+        assert_eq!(
+            parse_type(input).unwrap_err(),
+            TypeError {
+                rust_type: input.to_string(),
+                reason: reason.to_string(),
+            }
+        );
+    }
+
+    #[rstest]
+    #[case("T", "f32")]
+    #[case("&[T]", "&[f32]")]
+    #[case("&mut T", "&mut f32")]
+    #[case("HashMap<String, Vec<T>>", "HashMap<String, Vec<f32>>")]
+    #[case("U", "U")]
+    #[case("Result<T, String>", "Result<f32, String>")]
+    fn test_substitute(#[case] input: &str, #[case] expected: &str) {
+        let bindings = HashMap::from([("T".to_string(), parse_type("f32").unwrap())]);
+        assert_eq!(
+            parse_type(input).unwrap().substitute(&bindings).to_string(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_parse_type_invalid() {
+        let error = parse_type("Vec<").unwrap_err();
+        assert_eq!(error.rust_type, "Vec<");
+    }
+}
@@ -0,0 +1,486 @@
+use std::{
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    functions::{
+        JNI_FALLIBLE_CALL_TEMPLATE, JNI_FUNCTION_TEMPLATE, JNI_RESULT_CALL_TEMPLATE,
+        KOTLIN_CATCHING_TEMPLATE, KOTLIN_FUNCTION_TEMPLATE, KOTLIN_WRAPPER_TEMPLATE,
+    },
+    kotlin::{KOTLIN_OBJECT_TEMPLATE, KOTLIN_ROOT_OBJECT_TEMPLATE},
+};
+
+/// An error in a template.
+#[derive(Debug, PartialEq)]
+pub enum TemplateError {
+    /// The template uses a variable that is not given to it.
+    UnknownVariable(String),
+    /// The template does not use a variable that the generated code needs.
+    MissingVariable(String),
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TemplateError::UnknownVariable(name) => write!(f, "Unknown variable `{{{}}}`", name),
+            TemplateError::MissingVariable(name) => {
+                write!(f, "The required variable `{{{}}}` is not used", name)
+            }
+        }
+    }
+}
+
+/// An error in a template file given with `--templates`.
+#[derive(Debug)]
+pub struct TemplateFileError {
+    pub path: PathBuf,
+    pub reason: String,
+}
+
+impl fmt::Display for TemplateFileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.path.display(), self.reason)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Segment {
+    Text(String),
+    /// A variable, along with the indentation of the line it is on.
+    Variable {
+        name: String,
+        indent: String,
+    },
+}
+
+/// A template of generated code, in which `{name}` is replaced by the value of the variable
+/// `name`. `{{` and `}}` are a literal `{` and `}`, and any other brace is literal too, so braces
+/// of Rust and Kotlin code only need to be escaped when they surround a name.
+///
+/// Values are inserted as they are, so a value containing `{name}` is never replaced in turn.
+/// The lines of a value after the first are indented like the line of the template the variable
+/// is on, so multi-line values are written without indentation.
+#[derive(Debug)]
+pub struct Template {
+    segments: Vec<Segment>,
+}
+
+impl Template {
+    /// Parses a template that may use any of `variables`.
+    pub fn parse(source: &str, variables: &[&str]) -> Result<Template, TemplateError> {
+        let mut segments = Vec::new();
+        let mut text = String::new();
+        let mut line_start = 0;
+        let mut rest = source;
+
+        while let Some(c) = rest.chars().next() {
+            if rest.starts_with("{{") || rest.starts_with("}}") {
+                text.push(c);
+                rest = &rest[2..];
+                continue;
+            }
+            if let Some(name) = rest.strip_prefix('{').and_then(parse_variable) {
+                if !variables.contains(&name) {
+                    return Err(TemplateError::UnknownVariable(name.to_string()));
+                }
+                let position = source.len() - rest.len();
+                let indent = source[line_start..position]
+                    .chars()
+                    .take_while(|c| *c == ' ' || *c == '\t')
+                    .collect();
+                segments.push(Segment::Text(std::mem::take(&mut text)));
+                segments.push(Segment::Variable {
+                    name: name.to_string(),
+                    indent,
+                });
+                rest = &rest[name.len() + 2..];
+                continue;
+            }
+            if c == '\n' {
+                line_start = source.len() - rest.len() + 1;
+            }
+            text.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+        segments.push(Segment::Text(text));
+
+        Ok(Template { segments })
+    }
+
+    /// Parses a template that may use any of `variables`, and must use each of `required`.
+    fn parse_required(
+        source: &str,
+        variables: &[&str],
+        required: &[&str],
+    ) -> Result<Template, TemplateError> {
+        let template = Template::parse(source, variables)?;
+        for name in required {
+            let used = template
+                .segments
+                .iter()
+                .any(|s| matches!(s, Segment::Variable { name: n, .. } if n == name));
+            if !used {
+                return Err(TemplateError::MissingVariable(name.to_string()));
+            }
+        }
+        Ok(template)
+    }
+
+    /// Renders the template. Every variable the template uses must have a value.
+    pub fn render(&self, values: &[(&str, &str)]) -> String {
+        let mut result = String::new();
+        for segment in &self.segments {
+            match segment {
+                Segment::Text(text) => result.push_str(text),
+                Segment::Variable { name, indent } => {
+                    let (_, value) = values
+                        .iter()
+                        .find(|(n, _)| n == name)
+                        .unwrap_or_else(|| panic!("No value for the template variable {}", name));
+                    result.push_str(&indent_lines(value, indent));
+                }
+            }
+        }
+        result
+    }
+}
+
+/// A template that users can override with a `{name}.template` file.
+struct TemplateSpec {
+    name: &'static str,
+    source: &'static str,
+    variables: &'static [&'static str],
+    /// Variables without which the generated code is invalid.
+    required: &'static [&'static str],
+}
+
+const JNI_FUNCTION: TemplateSpec = TemplateSpec {
+    name: "jni_function",
+    source: JNI_FUNCTION_TEMPLATE,
+    variables: &[
+        "docs",
+        "name",
+        "params",
+        "ret_type",
+        "setup",
+        "result_assignment",
+        "call",
+        "write_back",
+        "return",
+    ],
+    required: &[
+        "name",
+        "params",
+        "ret_type",
+        "setup",
+        "result_assignment",
+        "call",
+        "write_back",
+        "return",
+    ],
+};
+
+const JNI_FALLIBLE_CALL: TemplateSpec = TemplateSpec {
+    name: "jni_fallible_call",
+    source: JNI_FALLIBLE_CALL_TEMPLATE,
+    variables: &["func_name", "args", "default"],
+    required: &["func_name", "args", "default"],
+};
+
+const JNI_RESULT_CALL: TemplateSpec = TemplateSpec {
+    name: "jni_result_call",
+    source: JNI_RESULT_CALL_TEMPLATE,
+    variables: &["call", "default"],
+    required: &["call", "default"],
+};
+
+const KOTLIN_FUNCTION: TemplateSpec = TemplateSpec {
+    name: "kotlin_function",
+    source: KOTLIN_FUNCTION_TEMPLATE,
+    variables: &["docs", "name", "params", "ret_type"],
+    required: &["name", "params", "ret_type"],
+};
+
+const KOTLIN_WRAPPER: TemplateSpec = TemplateSpec {
+    name: "kotlin_wrapper",
+    source: KOTLIN_WRAPPER_TEMPLATE,
+    variables: &[
+        "docs",
+        "name",
+        "params",
+        "external_name",
+        "external_params",
+        "ret_type",
+        "args",
+    ],
+    required: &[
+        "name",
+        "params",
+        "external_name",
+        "external_params",
+        "ret_type",
+        "args",
+    ],
+};
+
+const KOTLIN_CATCHING: TemplateSpec = TemplateSpec {
+    name: "kotlin_catching",
+    source: KOTLIN_CATCHING_TEMPLATE,
+    variables: &["name", "catching_name", "params", "result_type", "args"],
+    required: &["name", "catching_name", "params", "result_type", "args"],
+};
+
+const KOTLIN_ROOT_OBJECT: TemplateSpec = TemplateSpec {
+    name: "kotlin_root_object",
+    source: KOTLIN_ROOT_OBJECT_TEMPLATE,
+    variables: &["package_name", "name", "library_name", "contents"],
+    required: &["package_name", "name", "library_name", "contents"],
+};
+
+const KOTLIN_OBJECT: TemplateSpec = TemplateSpec {
+    name: "kotlin_object",
+    source: KOTLIN_OBJECT_TEMPLATE,
+    variables: &["name", "contents"],
+    required: &["name", "contents"],
+};
+
+const TEMPLATE_SPECS: [&TemplateSpec; 8] = [
+    &JNI_FUNCTION,
+    &JNI_FALLIBLE_CALL,
+    &JNI_RESULT_CALL,
+    &KOTLIN_FUNCTION,
+    &KOTLIN_WRAPPER,
+    &KOTLIN_CATCHING,
+    &KOTLIN_ROOT_OBJECT,
+    &KOTLIN_OBJECT,
+];
+
+/// The templates of the generated functions, objects and files.
+#[derive(Debug)]
+pub struct Templates {
+    pub jni_function: Template,
+    pub jni_fallible_call: Template,
+    pub jni_result_call: Template,
+    pub kotlin_function: Template,
+    pub kotlin_wrapper: Template,
+    pub kotlin_catching: Template,
+    pub kotlin_root_object: Template,
+    pub kotlin_object: Template,
+}
+
+impl Templates {
+    /// Loads the templates, overriding the built-in ones with the `{name}.template` files in
+    /// `directory`.
+    pub fn load(directory: Option<&Path>) -> Result<Templates, TemplateFileError> {
+        if let Some(directory) = directory {
+            check_template_files(directory)?;
+        }
+        let load = |spec: &TemplateSpec| load_template(spec, directory);
+        Ok(Templates {
+            jni_function: load(&JNI_FUNCTION)?,
+            jni_fallible_call: load(&JNI_FALLIBLE_CALL)?,
+            jni_result_call: load(&JNI_RESULT_CALL)?,
+            kotlin_function: load(&KOTLIN_FUNCTION)?,
+            kotlin_wrapper: load(&KOTLIN_WRAPPER)?,
+            kotlin_catching: load(&KOTLIN_CATCHING)?,
+            kotlin_root_object: load(&KOTLIN_ROOT_OBJECT)?,
+            kotlin_object: load(&KOTLIN_OBJECT)?,
+        })
+    }
+}
+
+impl Default for Templates {
+    fn default() -> Self {
+        Templates::load(None).unwrap_or_else(|e| panic!("Invalid built-in template: {}", e))
+    }
+}
+
+fn load_template(
+    spec: &TemplateSpec,
+    directory: Option<&Path>,
+) -> Result<Template, TemplateFileError> {
+    let path = directory.map(|d| d.join(format!("{}.template", spec.name)));
+    let source = match &path {
+        Some(path) if path.exists() => fs::read_to_string(path).map_err(|e| TemplateFileError {
+            path: path.clone(),
+            reason: e.to_string(),
+        })?,
+        _ => spec.source.to_string(),
+    };
+    Template::parse_required(&source, spec.variables, spec.required).map_err(|e| {
+        TemplateFileError {
+            path: path
+                .filter(|p| p.exists())
+                .unwrap_or_else(|| PathBuf::from(spec.name)),
+            reason: e.to_string(),
+        }
+    })
+}
+
+/// Rejects `.template` files that do not override any template, such as a misspelled name.
+fn check_template_files(directory: &Path) -> Result<(), TemplateFileError> {
+    let entries = fs::read_dir(directory).map_err(|e| TemplateFileError {
+        path: directory.to_path_buf(),
+        reason: e.to_string(),
+    })?;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().is_none_or(|e| e != "template") {
+            continue;
+        }
+        let name = path.file_stem().unwrap_or_default().to_string_lossy();
+        if !TEMPLATE_SPECS.iter().any(|s| s.name == name) {
+            return Err(TemplateFileError {
+                path: path.clone(),
+                reason: format!(
+                    "Unknown template, expected one of {}",
+                    TEMPLATE_SPECS
+                        .iter()
+                        .map(|s| format!("`{}`", s.name))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Parses the name of a variable and its closing brace, after an opening brace.
+fn parse_variable(rest: &str) -> Option<&str> {
+    let length = rest
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+        .unwrap_or(rest.len());
+    let name = &rest[..length];
+    let starts_with_letter = name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_');
+    if starts_with_letter && rest[length..].starts_with('}') {
+        Some(name)
+    } else {
+        None
+    }
+}
+
+/// Indents the lines of a value after the first. Empty lines are left empty, except the last one,
+/// since the template continues on it.
+fn indent_lines(value: &str, indent: &str) -> String {
+    let lines = value.split('\n').collect::<Vec<_>>();
+    let last = lines.len() - 1;
+    lines
+        .iter()
+        .enumerate()
+        .map(|(i, line)| {
+            if i == 0 || (line.is_empty() && i != last) {
+                line.to_string()
+            } else {
+                format!("{}{}", indent, line)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Renders a template of the generator, which uses only the variables given to it.
+pub fn render(source: &str, values: &[(&str, &str)]) -> String {
+    let variables = values.iter().map(|(name, _)| *name).collect::<Vec<_>>();
+    let template =
+        Template::parse(source, &variables).unwrap_or_else(|e| panic!("Invalid template: {}", e));
+    template.render(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("fn {name}() {}", "fn add() {}")]
+    #[case("{{name}} is {name}", "{name} is add")]
+    #[case("}} { name }", "} { name }")]
+    #[case("{value}{name}", "{name}add")]
+    #[case("object {name} {\n}", "object add {\n}")]
+    fn test_render(#[case] source: &str, #[case] expected: &str) {
+        assert_eq!(
+            render(source, &[("name", "add"), ("value", "{name}")]),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_render_indents_values() {
+        let source = "object A {\n    {contents}\n}\n\nfn f() {\n    {setup}{call};\n}";
+        let contents = "fun a()\n\nobject B {\n    fun b()\n}";
+        let setup = "let a = {\n    1\n};\n";
+        assert_eq!(
+            render(
+                source,
+                &[("contents", contents), ("setup", setup), ("call", "f(a)")]
+            ),
+            r#"object A {
+    fun a()
+
+    object B {
+        fun b()
+    }
+}
+
+fn f() {
+    let a = {
+        1
+    };
+    f(a);
+}"#
+        );
+    }
+
+    #[test]
+    fn test_parse_unknown_variable() {
+        assert_eq!(
+            Template::parse("fun {name}({params})", &["name"]).unwrap_err(),
+            TemplateError::UnknownVariable("params".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_missing_variable() {
+        assert_eq!(
+            Template::parse_required("fun {name}()", &["name", "params"], &["name", "params"])
+                .unwrap_err(),
+            TemplateError::MissingVariable("params".to_string())
+        );
+    }
+
+    #[test]
+    fn test_load_templates() {
+        let directory = std::env::temp_dir().join(format!("templates-{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        fs::write(
+            directory.join("kotlin_function.template"),
+            "@Keep\nexternal fun {name}({params}){ret_type}",
+        )
+        .unwrap();
+        let templates = Templates::load(Some(&directory)).unwrap();
+        assert_eq!(
+            templates.kotlin_function.render(&[
+                ("name", "add"),
+                ("params", ""),
+                ("ret_type", ": Int")
+            ]),
+            "@Keep\nexternal fun add(): Int"
+        );
+
+        fs::write(directory.join("kotlin_objects.template"), "").unwrap();
+        let error = Templates::load(Some(&directory)).unwrap_err();
+        assert!(error.path.ends_with("kotlin_objects.template"));
+        fs::remove_file(directory.join("kotlin_objects.template")).unwrap();
+
+        fs::write(directory.join("kotlin_object.template"), "object {name}").unwrap();
+        let error = Templates::load(Some(&directory)).unwrap_err();
+        assert_eq!(
+            error.reason,
+            "The required variable `{contents}` is not used"
+        );
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
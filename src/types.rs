@@ -1,69 +1,836 @@
-// use crate::converters::get_converter;
-
-pub fn get_jni_type(rust_type: &str) -> String {
-    // return get_converter(rust_type).get_jni_type();
-
-    let jtype = match rust_type {
-        "i8" => "jbyte",
-        "i16" => "jshort",
-        "i32" => "jint",
-        "i64" => "jlong",
-        "u8" => "jbyte",
-        "u16" => "jshort",
-        "u32" => "jint",
-        "u64" => "jlong",
-        "f32" => "jfloat",
-        "f64" => "jdouble",
-        "bool" => "jboolean",
-        "char" => "jchar",
-        // TODO: Handle arrays, strings, and objects
-        _ => return rust_type.to_string(),
+use serde_json::Value;
+
+use crate::{
+    rust_type::{MapKind, RustType},
+    template::render,
+};
+
+/// The supported Rust types and the Kotlin types they are bound to.
+pub const SUPPORTED_TYPES: [(&str, &str); 17] = [
+    ("i8, u8", "Byte"),
+    ("i16, u16", "Short"),
+    ("i32, u32", "Int"),
+    ("i64, u64", "Long"),
+    ("f32", "Float"),
+    ("f64", "Double"),
+    ("bool", "Boolean"),
+    ("char", "Char"),
+    ("String", "String"),
+    (
+        "HashMap<K, V>",
+        "Map<K, V> (a java.util.HashMap when returned)",
+    ),
+    (
+        "BTreeMap<K, V>",
+        "Map<K, V> (a java.util.LinkedHashMap when returned, preserving order)",
+    ),
+    (
+        "Vec<T> of a primitive",
+        "The matching primitive array, such as FloatArray",
+    ),
+    (
+        "Vec<T> of any other supported type",
+        "Array<T>, such as Array<String> or Array<FloatArray>",
+    ),
+    ("&str", "String"),
+    ("&[T]", "The same as Vec<T>"),
+    (
+        "&mut T of a primitive",
+        "A single element primitive array, such as FloatArray",
+    ),
+    (
+        "&mut [T] of a primitive",
+        "The matching primitive array, such as FloatArray",
+    ),
+];
+
+/// How a Rust primitive is represented on the Java side.
+struct JavaPrimitive {
+    java_name: &'static str,
+    signature: &'static str,
+    boxed_class: &'static str,
+}
+
+impl JavaPrimitive {
+    fn jni_type(&self) -> String {
+        format!("j{}", self.java_name)
+    }
+
+    fn kotlin_type(&self) -> String {
+        let mut chars = self.java_name.chars();
+        chars
+            .next()
+            .map(|first| first.to_uppercase().chain(chars).collect())
+            .unwrap_or_default()
+    }
+
+    fn array_wrapper(&self) -> String {
+        format!("J{}Array", self.kotlin_type())
+    }
+
+    /// The `JValue` variant holding this primitive.
+    fn value_variant(&self) -> String {
+        match self.java_name {
+            "boolean" => "Bool".to_string(),
+            _ => self.kotlin_type(),
+        }
+    }
+
+    /// The `JValueOwned` accessor, which is named after the type descriptor.
+    fn accessor(&self) -> String {
+        self.signature.to_lowercase()
+    }
+}
+
+fn get_primitive(rust_type: &RustType) -> Option<JavaPrimitive> {
+    let RustType::Named(name) = rust_type else {
+        return None;
+    };
+    let (java_name, signature, boxed_class) = match name.as_str() {
+        "i8" | "u8" => ("byte", "B", "java/lang/Byte"),
+        "i16" | "u16" => ("short", "S", "java/lang/Short"),
+        "i32" | "u32" => ("int", "I", "java/lang/Integer"),
+        "i64" | "u64" => ("long", "J", "java/lang/Long"),
+        "f32" => ("float", "F", "java/lang/Float"),
+        "f64" => ("double", "D", "java/lang/Double"),
+        "bool" => ("boolean", "Z", "java/lang/Boolean"),
+        "char" => ("char", "C", "java/lang/Character"),
+        _ => return None,
     };
-    String::from(jtype)
+    Some(JavaPrimitive {
+        java_name,
+        signature,
+        boxed_class,
+    })
+}
+
+fn is_string(rust_type: &RustType) -> bool {
+    matches!(rust_type, RustType::Named(name) if name == "String")
+}
+
+/// Checks that the type of an input, or of an output, can be bound, returning the reason when it
+/// cannot, such as for a struct.
+pub fn check_type(rust_type: &RustType, is_input: bool) -> Result<(), String> {
+    match rust_type {
+        RustType::Reference {
+            mutable: true,
+            inner,
+        } => {
+            if !is_input {
+                return Err("mutable references cannot be returned".to_string());
+            }
+            match inner.as_ref() {
+                // The values are written back to the Java array, whose length cannot change
+                RustType::Vec(_) => Err(
+                    "mutable references to Vecs are not supported, since a Java array cannot change its length; use a mutable slice instead"
+                        .to_string(),
+                ),
+                RustType::Slice(element) if get_primitive(element).is_some() => Ok(()),
+                inner if get_primitive(inner).is_some() => Ok(()),
+                _ => Err(
+                    "only mutable references to primitives, or to slices of primitives, are supported"
+                        .to_string(),
+                ),
+            }
+        }
+        RustType::Result(..) if is_input => Err("Results can only be returned".to_string()),
+        RustType::Result(ok, _) => match ok.as_deref() {
+            Some(RustType::Result(..)) => Err("nested Results are not supported".to_string()),
+            Some(ok) => check_type(ok, false),
+            None => Ok(()),
+        },
+        _ => check_owned_type(&rust_type.to_owned_type()),
+    }
+}
+
+fn check_owned_type(rust_type: &RustType) -> Result<(), String> {
+    if get_primitive(rust_type).is_some() || is_string(rust_type) {
+        return Ok(());
+    }
+    match rust_type {
+        RustType::Vec(element) => check_owned_type(element),
+        RustType::Map(_, key, value) => {
+            check_owned_type(key)?;
+            check_owned_type(value)
+        }
+        // Records are not bound yet, so they cannot be converted, even in a collection
+        RustType::Named(_) => Err(format!(
+            "`{}` is not a supported type, and structs and enums cannot be bound yet",
+            rust_type
+        )),
+        _ => Err(format!("`{}` is not a supported type", rust_type)),
+    }
+}
+
+/// The reason code cannot be generated for a type, which `check_type` should have rejected.
+fn unsupported(rust_type: &RustType) -> String {
+    format!("`{}` is not a supported type", rust_type)
+}
+
+/// The JVM type descriptor, such as `I`, `[F` or `Ljava/lang/String;`.
+fn get_signature(rust_type: &RustType) -> Result<String, String> {
+    if let Some(primitive) = get_primitive(rust_type) {
+        return Ok(primitive.signature.to_string());
+    }
+    match rust_type {
+        _ if is_string(rust_type) => Ok("Ljava/lang/String;".to_string()),
+        RustType::Vec(element) => Ok(format!("[{}", get_signature(element)?)),
+        RustType::Map(..) => Ok("Ljava/util/Map;".to_string()),
+        _ => Err(unsupported(rust_type)),
+    }
+}
+
+/// The type descriptor of an input, used to build the JNI symbol of an overloaded function.
+pub fn get_jni_input_signature(rust_type: &RustType) -> Result<String, String> {
+    get_signature(&get_java_input_type(rust_type)?)
+}
+
+/// The class name passed to `FindClass`, which uses descriptors for array classes.
+fn get_class_name(rust_type: &RustType) -> Result<String, String> {
+    let signature = get_signature(rust_type)?;
+    Ok(match signature.strip_prefix('L') {
+        Some(class) => class.trim_end_matches(';').to_string(),
+        None => signature,
+    })
 }
 
-pub fn convert_java_type_to_rust(name: &str, rust_type: &str) -> String {
-    // unsigned values require .try_into().unwrap(), all others can pass through for now
-    let conversion = match rust_type {
-        "u8" | "u16" | "u32" | "u64" => format!("{}.try_into().unwrap()", name),
-        _ => name.to_string(),
+pub fn get_jni_type(rust_type: &RustType) -> Result<String, String> {
+    get_raw_jni_type(&rust_type.to_owned_type())
+}
+
+fn get_raw_jni_type(rust_type: &RustType) -> Result<String, String> {
+    if let Some(primitive) = get_primitive(rust_type) {
+        return Ok(primitive.jni_type());
+    }
+    match rust_type {
+        _ if is_string(rust_type) => Ok("jstring".to_string()),
+        RustType::Vec(element) => Ok(match get_primitive(element) {
+            Some(primitive) => format!("{}Array", primitive.jni_type()),
+            None => "jobjectArray".to_string(),
+        }),
+        RustType::Map(..) => Ok("jobject".to_string()),
+        _ => Err(unsupported(rust_type)),
+    }
+}
+
+/// Converts a default value from the config to a Kotlin literal of the type of an input, returning
+/// the reason when the value does not fit the type.
+pub fn get_kotlin_default_value(rust_type: &RustType, value: &Value) -> Result<String, String> {
+    if let RustType::Reference { mutable: true, .. } = rust_type {
+        return Err("mutable references cannot have a default value".to_string());
+    }
+    let rust_type = rust_type.to_owned_type();
+    if is_string(&rust_type) {
+        return match value {
+            Value::String(string) => Ok(format!(
+                "\"{}\"",
+                string
+                    .chars()
+                    .map(|c| escape_kotlin_char(c, '"'))
+                    .collect::<String>()
+            )),
+            _ => Err(format!("expected a string, found {}", value)),
+        };
+    }
+    let RustType::Named(name) = &rust_type else {
+        return Err(format!("`{}` cannot have a default value", rust_type));
     };
-    conversion
+
+    match name.as_str() {
+        "i8" | "i16" | "i32" | "i64" | "u8" | "u16" | "u32" | "u64" => {
+            let bits = name[1..].parse::<u32>().unwrap();
+            let (min, max) = if name.starts_with('u') {
+                (0, (1i128 << bits) - 1)
+            } else {
+                (-(1i128 << (bits - 1)), (1i128 << (bits - 1)) - 1)
+            };
+            let integer = value
+                .as_i64()
+                .map(i128::from)
+                .or_else(|| value.as_u64().map(i128::from))
+                .ok_or_else(|| format!("expected an integer, found {}", value))?;
+            if integer < min || integer > max {
+                return Err(format!("{} is out of the range of `{}`", integer, name));
+            }
+            // Unsigned integers are passed as the signed JVM integer with the same bits
+            let jvm_integer = if integer >= 1 << (bits - 1) {
+                integer - (1 << bits)
+            } else {
+                integer
+            };
+            if jvm_integer == -(1 << (bits - 1)) {
+                // The literal of the minimum value is out of range before it is negated
+                Ok(format!("{}.MIN_VALUE", get_kotlin_type(&rust_type)?))
+            } else {
+                Ok(jvm_integer.to_string())
+            }
+        }
+        "f32" | "f64" => {
+            let float = value
+                .as_f64()
+                .ok_or_else(|| format!("expected a number, found {}", value))?;
+            if name == "f32" {
+                // The number is formatted here, since serde_json versions print exponents differently
+                if float.abs() > f32::MAX as f64 {
+                    return Err(format!("{:e} is out of the range of `f32`", float));
+                }
+                Ok(format!("{:?}f", float as f32))
+            } else {
+                Ok(format!("{:?}", float))
+            }
+        }
+        "bool" => match value {
+            Value::Bool(boolean) => Ok(boolean.to_string()),
+            _ => Err(format!("expected true or false, found {}", value)),
+        },
+        "char" => {
+            let mut chars = value.as_str().unwrap_or_default().chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) if (c as u32) <= u16::MAX as u32 => {
+                    Ok(format!("'{}'", escape_kotlin_char(c, '\'')))
+                }
+                (Some(c), None) => Err(format!("{:?} does not fit in a Kotlin `Char`", c)),
+                _ => Err(format!(
+                    "expected a string of one character, found {}",
+                    value
+                )),
+            }
+        }
+        _ => Err(format!("`{}` cannot have a default value", rust_type)),
+    }
 }
 
-pub fn convert_rust_type_to_java(name: &str, rust_type: &str) -> String {
-    // unsigned values require .try_into().unwrap(), all others can pass through for now
-    let conversion = match rust_type {
-        "u8" | "u16" | "u32" | "u64" => format!("{}.try_into().unwrap()", name),
-        _ => name.to_string(),
+/// Escapes a character in a Kotlin string or character literal delimited by `quote`.
+fn escape_kotlin_char(c: char, quote: char) -> String {
+    match c {
+        '\\' => "\\\\".to_string(),
+        '\n' => "\\n".to_string(),
+        '\r' => "\\r".to_string(),
+        '\t' => "\\t".to_string(),
+        '\u{8}' => "\\b".to_string(),
+        // `$` starts a template in strings
+        '$' if quote == '"' => "\\$".to_string(),
+        _ if c == quote => format!("\\{}", c),
+        _ if c.is_control() => format!("\\u{:04x}", c as u32),
+        _ => c.to_string(),
+    }
+}
+
+/// The value returned to the JVM when an exception is thrown instead of returning a result.
+pub fn get_jni_default_value(rust_type: &RustType) -> String {
+    match get_primitive(&rust_type.to_owned_type()) {
+        Some(_) => "Default::default()".to_string(),
+        None => "std::ptr::null_mut()".to_string(),
+    }
+}
+
+pub fn get_jni_input_type(rust_type: &RustType) -> Result<String, String> {
+    get_jni_wrapper_type(&get_java_input_type(rust_type)?)
+}
+
+/// The type of an input on the Java side. Mutable references are passed as arrays so that changes
+/// can be written back, using a single element array for a mutable reference to a primitive.
+fn get_java_input_type(rust_type: &RustType) -> Result<RustType, String> {
+    match rust_type {
+        RustType::Reference {
+            mutable: true,
+            inner,
+        } => match inner.to_owned_type() {
+            RustType::Vec(element) if get_primitive(&element).is_some() => {
+                Ok(RustType::Vec(element))
+            }
+            owned if get_primitive(&owned).is_some() => Ok(RustType::Vec(Box::new(owned))),
+            _ => Err(unsupported(rust_type)),
+        },
+        _ => Ok(rust_type.to_owned_type()),
+    }
+}
+
+/// The primitive behind a mutable reference input, and whether it is a single value.
+fn get_mutable_primitive(
+    rust_type: &RustType,
+) -> Result<Option<(RustType, JavaPrimitive, bool)>, String> {
+    let RustType::Reference {
+        mutable: true,
+        inner,
+    } = rust_type
+    else {
+        return Ok(None);
+    };
+    let RustType::Vec(element) = get_java_input_type(rust_type)? else {
+        return Err(unsupported(rust_type));
+    };
+    let primitive = get_primitive(&element).ok_or_else(|| unsupported(rust_type))?;
+    let is_scalar = get_primitive(inner).is_some();
+    Ok(Some((*element, primitive, is_scalar)))
+}
+
+/// Statements run before the call, converting a reference input into a local variable that is
+/// borrowed by the call, and outlives a result borrowing from it.
+pub fn get_input_setup(name: &str, rust_type: &RustType) -> Result<Option<String>, String> {
+    if let RustType::Reference {
+        mutable: false,
+        inner,
+    } = rust_type
+    {
+        let owned = inner.to_owned_type();
+        return Ok(Some(format!(
+            "let {name}_value: {owned} = {};",
+            convert_jni_to_rust(name, &owned)?
+        )));
+    }
+
+    let Some((element, primitive, is_scalar)) = get_mutable_primitive(rust_type)? else {
+        return Ok(None);
+    };
+    Ok(Some(if is_scalar {
+        render(
+            r#"let mut {name}_value = {
+    let mut buffer = [{jni_type}::default(); 1];
+    env.get_{java_name}_array_region(&{name}, 0, &mut buffer).unwrap();
+    {value}
+};"#,
+            &[
+                ("name", name),
+                ("jni_type", &primitive.jni_type()),
+                ("java_name", primitive.java_name),
+                ("value", &convert_jni_to_rust("buffer[0]", &element)?),
+            ],
+        )
+    } else {
+        let vec = RustType::Vec(Box::new(element));
+        format!(
+            "let mut {name}_value: {vec} = {};",
+            convert_jni_to_rust(name, &vec)?
+        )
+    }))
+}
+
+/// Statements run after the call, writing a mutable reference input back to the Java array.
+pub fn get_input_write_back(name: &str, rust_type: &RustType) -> Result<Option<String>, String> {
+    let Some((element, primitive, is_scalar)) = get_mutable_primitive(rust_type)? else {
+        return Ok(None);
     };
-    conversion
-}
-
-pub fn get_kotlin_type(rust_type: &str) -> String {
-    let kotlin_type = match rust_type {
-        "i8" => "Byte",
-        "i16" => "Short",
-        "i32" => "Int",
-        "i64" => "Long",
-        "u8" => "Byte",
-        "u16" => "Short",
-        "u32" => "Int",
-        "u64" => "Long",
-        "f32" => "Float",
-        "f64" => "Double",
-        "bool" => "Boolean",
-        "char" => "Char",
-        // TODO: Handle arrays, strings, and objects
-        _ => panic!("Unsupported type: {}", rust_type),
+    let value = format!("{}_value", name);
+    let buffer = if is_scalar {
+        format!("[{}]", convert_rust_to_jni(&value, &element)?)
+    } else {
+        match convert_rust_to_jni("value", &element)?.as_str() {
+            "value" => value,
+            converted => format!(
+                "{}.into_iter().map(|value| {}).collect::<Vec<_>>()",
+                value, converted
+            ),
+        }
     };
-    String::from(kotlin_type)
+    Ok(Some(format!(
+        "env.set_{}_array_region(&{}, 0, &{}).unwrap();",
+        primitive.java_name, name, buffer
+    )))
+}
+
+/// A condition that is true when a Rust value has a `char` outside the Basic Multilingual Plane,
+/// such as '😀', which does not fit in a Java `char`. Returns `None` when the type has no `char`.
+pub fn get_wide_char_check(name: &str, rust_type: &RustType) -> Result<Option<String>, String> {
+    // Elements are borrowed by `iter`, so a `char` element is dereferenced
+    let element_name = |base: &str, element: &RustType| match element {
+        RustType::Named(_) => format!("*{}", base),
+        _ => base.to_string(),
+    };
+    match rust_type {
+        RustType::Named(n) if n == "char" => Ok(Some(format!("u32::from({}) > 0xFFFF", name))),
+        RustType::Named(_) => Ok(None),
+        RustType::Reference { inner, .. } => get_wide_char_check(&element_name(name, inner), inner),
+        RustType::Result(..) => Err(unsupported(rust_type)),
+        RustType::Slice(element) | RustType::Vec(element) => Ok(get_wide_char_check(
+            &element_name("value", element),
+            element,
+        )?
+        .map(|check| format!("{}.iter().any(|value| {})", name, check))),
+        RustType::Map(_, key, value) => {
+            let key_check = get_wide_char_check(&element_name("key", key), key)?;
+            let value_check = get_wide_char_check(&element_name("value", value), value)?;
+            let pattern = format!(
+                "({}, {})",
+                if key_check.is_some() { "key" } else { "_" },
+                if value_check.is_some() { "value" } else { "_" }
+            );
+            let checks = [key_check, value_check];
+            let checks = checks.into_iter().flatten().collect::<Vec<_>>();
+            if checks.is_empty() {
+                return Ok(None);
+            }
+            Ok(Some(format!(
+                "{}.iter().any(|{}| {})",
+                name,
+                pattern,
+                checks.join(" || ")
+            )))
+        }
+    }
+}
+
+/// The `jni::objects` wrapper for object types, or the raw type for primitives.
+fn get_jni_wrapper_type(rust_type: &RustType) -> Result<String, String> {
+    match rust_type {
+        _ if is_string(rust_type) => Ok("JString".to_string()),
+        RustType::Vec(element) => Ok(match get_primitive(element) {
+            Some(primitive) => primitive.array_wrapper(),
+            None => "JObjectArray".to_string(),
+        }),
+        RustType::Map(..) => Ok("JObject".to_string()),
+        _ => get_raw_jni_type(rust_type),
+    }
+}
+
+pub fn convert_java_type_to_rust(name: &str, rust_type: &RustType) -> Result<String, String> {
+    match rust_type {
+        // The value was converted into a local by `get_input_setup`, and is borrowed relying on
+        // deref coercion for `&str` and `&[T]`
+        RustType::Reference { mutable: true, .. } => Ok(format!("&mut {}_value", name)),
+        RustType::Reference { .. } => Ok(format!("&{}_value", name)),
+        _ => convert_jni_to_rust(name, rust_type),
+    }
+}
+
+fn convert_jni_to_rust(name: &str, rust_type: &RustType) -> Result<String, String> {
+    // Unsigned values are cast from the signed JVM integer with the same bits, so that values
+    // above the signed maximum, such as bytes from 0x80, round trip
+    match rust_type {
+        RustType::Named(n) => match n.as_str() {
+            "u8" | "u16" | "u32" | "u64" => Ok(format!("{} as {}", name, n)),
+            "bool" => Ok(format!("{} != 0", name)),
+            // A Java char can be a lone surrogate, which is not a Rust char
+            "char" => Ok(format!(
+                "char::from_u32({}.into()).unwrap_or(char::REPLACEMENT_CHARACTER)",
+                name
+            )),
+            "String" => Ok(format!("String::from(env.get_string(&{}).unwrap())", name)),
+            _ if get_primitive(rust_type).is_some() => Ok(name.to_string()),
+            _ => Err(unsupported(rust_type)),
+        },
+        RustType::Vec(element) => match get_primitive(element) {
+            Some(primitive) => convert_java_primitive_array_to_rust(name, element, &primitive),
+            None => convert_java_object_array_to_rust(name, element),
+        },
+        RustType::Map(kind, key, value) => convert_java_map_to_rust(name, kind, key, value),
+        _ => Err(unsupported(rust_type)),
+    }
+}
+
+pub fn convert_rust_type_to_java(name: &str, rust_type: &RustType) -> Result<String, String> {
+    match rust_type {
+        RustType::Reference { mutable: true, .. } => Err(unsupported(rust_type)),
+        RustType::Reference { .. } => {
+            convert_rust_to_jni(&format!("{}.to_owned()", name), &rust_type.to_owned_type())
+        }
+        _ => convert_rust_to_jni(name, rust_type),
+    }
+}
+
+fn convert_rust_to_jni(name: &str, rust_type: &RustType) -> Result<String, String> {
+    // Unsigned values are cast to the signed JVM integer with the same bits
+    match rust_type {
+        RustType::Named(n) => match n.as_str() {
+            "u8" | "u16" | "u32" | "u64" => Ok(format!("{} as {}", name, n.replace('u', "i"))),
+            "bool" => Ok(format!("jboolean::from({})", name)),
+            "char" => Ok(format!("{} as jchar", name)),
+            "String" => convert_rust_to_jni_object(name, rust_type, ".into_raw()"),
+            _ if get_primitive(rust_type).is_some() => Ok(name.to_string()),
+            _ => Err(unsupported(rust_type)),
+        },
+        _ => convert_rust_to_jni_object(name, rust_type, ".into_raw()"),
+    }
+}
+
+/// Converts a Rust value into its `jni::objects` wrapper, followed by `suffix`.
+fn convert_rust_to_jni_object(
+    name: &str,
+    rust_type: &RustType,
+    suffix: &str,
+) -> Result<String, String> {
+    match rust_type {
+        _ if is_string(rust_type) => Ok(format!("env.new_string({}).unwrap(){}", name, suffix)),
+        RustType::Vec(element) => match get_primitive(element) {
+            Some(primitive) => {
+                convert_rust_primitive_array_to_java(name, element, &primitive, suffix)
+            }
+            None => convert_rust_object_array_to_java(name, element, suffix),
+        },
+        RustType::Map(kind, key, value) => convert_rust_map_to_java(name, kind, key, value, suffix),
+        _ => Err(unsupported(rust_type)),
+    }
+}
+
+/// Converts a `java.lang.Object` (such as a map key or array element) into the given Rust type.
+/// The object is only borrowed, so that its local reference can be deleted afterwards.
+fn convert_java_object_to_rust(name: &str, rust_type: &RustType) -> Result<String, String> {
+    if is_string(rust_type) {
+        return Ok(format!(
+            "String::from(env.get_string(<&JString>::from(&{})).unwrap())",
+            name
+        ));
+    }
+
+    if let Some(primitive) = get_primitive(rust_type) {
+        let unboxed = format!(
+            "env.call_method(&{}, \"{}Value\", \"(){}\", &[]).unwrap().{}().unwrap()",
+            name,
+            primitive.java_name,
+            primitive.signature,
+            primitive.accessor()
+        );
+        return match primitive.java_name {
+            // booleanValue is already returned as a bool
+            "boolean" => Ok(unboxed),
+            _ => convert_jni_to_rust(&unboxed, rust_type),
+        };
+    }
+
+    Ok(match get_jni_wrapper_type(rust_type)?.as_str() {
+        "JObject" => convert_jni_to_rust(name, rust_type)?,
+        wrapper => render(
+            "{\n    let {name} = <&{wrapper}>::from(&{name});\n    {value}\n}",
+            &[
+                ("name", name),
+                ("wrapper", wrapper),
+                (
+                    "value",
+                    &convert_jni_to_rust(&format!("*{}", name), rust_type)?,
+                ),
+            ],
+        ),
+    })
+}
+
+/// Converts a Rust value into a `java.lang.Object`, boxing primitives.
+fn convert_rust_object_to_java(name: &str, rust_type: &RustType) -> Result<String, String> {
+    Ok(match get_primitive(rust_type) {
+        Some(primitive) => format!(
+            "env.new_object(\"{}\", \"({})V\", &[JValue::{}({})]).unwrap()",
+            primitive.boxed_class,
+            primitive.signature,
+            primitive.value_variant(),
+            convert_rust_to_jni(name, rust_type)?
+        ),
+        None => format!(
+            "JObject::from({})",
+            convert_rust_to_jni_object(name, rust_type, "")?
+        ),
+    })
+}
+
+/// Converts each element of a buffer, or returns it unchanged when no conversion is needed.
+fn map_elements(buffer: &str, converted: &str) -> String {
+    if converted == "value" {
+        buffer.to_string()
+    } else {
+        format!(
+            "{}.into_iter().map(|value| {}).collect()",
+            buffer, converted
+        )
+    }
+}
+
+fn convert_java_primitive_array_to_rust(
+    name: &str,
+    element: &RustType,
+    primitive: &JavaPrimitive,
+) -> Result<String, String> {
+    Ok(render(
+        r#"{
+    let length = env.get_array_length(&{name}).unwrap();
+    let mut buffer = vec![{jni_type}::default(); length as usize];
+    env.get_{java_name}_array_region(&{name}, 0, &mut buffer).unwrap();
+    {result}
+}"#,
+        &[
+            ("name", name),
+            ("jni_type", &primitive.jni_type()),
+            ("java_name", primitive.java_name),
+            (
+                "result",
+                &map_elements("buffer", &convert_jni_to_rust("value", element)?),
+            ),
+        ],
+    ))
+}
+
+fn convert_rust_primitive_array_to_java(
+    name: &str,
+    element: &RustType,
+    primitive: &JavaPrimitive,
+    suffix: &str,
+) -> Result<String, String> {
+    Ok(render(
+        r#"{
+    let buffer: Vec<{jni_type}> = {buffer};
+    let array = env.new_{java_name}_array(buffer.len() as jsize).unwrap();
+    env.set_{java_name}_array_region(&array, 0, &buffer).unwrap();
+    array{suffix}
+}"#,
+        &[
+            ("jni_type", &primitive.jni_type()),
+            ("java_name", primitive.java_name),
+            (
+                "buffer",
+                &map_elements(name, &convert_rust_to_jni("value", element)?),
+            ),
+            ("suffix", suffix),
+        ],
+    ))
+}
+
+fn convert_java_object_array_to_rust(name: &str, element: &RustType) -> Result<String, String> {
+    Ok(render(
+        r#"{
+    let length = env.get_array_length(&{name}).unwrap();
+    let mut result = Vec::with_capacity(length as usize);
+    for i in 0..length {
+        let element = env.get_object_array_element(&{name}, i).unwrap();
+        result.push({element});
+        env.delete_local_ref(element).unwrap();
+    }
+    result
+}"#,
+        &[
+            ("name", name),
+            ("element", &convert_java_object_to_rust("element", element)?),
+        ],
+    ))
+}
+
+fn convert_rust_object_array_to_java(
+    name: &str,
+    element: &RustType,
+    suffix: &str,
+) -> Result<String, String> {
+    Ok(render(
+        r#"{
+    let elements = {name};
+    let array = env
+        .new_object_array(elements.len() as jsize, "{class}", JObject::null())
+        .unwrap();
+    for (i, element) in elements.into_iter().enumerate() {
+        let element = {element};
+        env.set_object_array_element(&array, i as jsize, &element).unwrap();
+        env.delete_local_ref(element).unwrap();
+    }
+    array{suffix}
+}"#,
+        &[
+            ("name", name),
+            ("class", &get_class_name(element)?),
+            ("element", &convert_rust_object_to_java("element", element)?),
+            ("suffix", suffix),
+        ],
+    ))
+}
+
+fn convert_java_map_to_rust(
+    name: &str,
+    kind: &MapKind,
+    key: &RustType,
+    value: &RustType,
+) -> Result<String, String> {
+    let constructor = match kind {
+        MapKind::Hash => "std::collections::HashMap::new()",
+        MapKind::BTree => "std::collections::BTreeMap::new()",
+    };
+    Ok(render(
+        r#"{
+    let map = JMap::from_env(&mut env, &{name}).unwrap();
+    let mut entries = map.iter(&mut env).unwrap();
+    let mut result = {constructor};
+    while let Some((key, value)) = entries.next(&mut env).unwrap() {
+        result.insert({key}, {value});
+        env.delete_local_ref(key).unwrap();
+        env.delete_local_ref(value).unwrap();
+    }
+    result
+}"#,
+        &[
+            ("name", name),
+            ("constructor", constructor),
+            ("key", &convert_java_object_to_rust("key", key)?),
+            ("value", &convert_java_object_to_rust("value", value)?),
+        ],
+    ))
+}
+
+fn convert_rust_map_to_java(
+    name: &str,
+    kind: &MapKind,
+    key: &RustType,
+    value: &RustType,
+    suffix: &str,
+) -> Result<String, String> {
+    // LinkedHashMap preserves the iteration order of a BTreeMap
+    let class = match kind {
+        MapKind::Hash => "java/util/HashMap",
+        MapKind::BTree => "java/util/LinkedHashMap",
+    };
+    Ok(render(
+        r#"{
+    let map = env.new_object("{class}", "()V", &[]).unwrap();
+    for (key, value) in {name} {
+        let key = {key};
+        let value = {value};
+        env.call_method(
+            &map,
+            "put",
+            "(Ljava/lang/Object;Ljava/lang/Object;)Ljava/lang/Object;",
+            &[JValue::Object(&key), JValue::Object(&value)],
+        )
+        .unwrap();
+        env.delete_local_ref(key).unwrap();
+        env.delete_local_ref(value).unwrap();
+    }
+    map{suffix}
+}"#,
+        &[
+            ("name", name),
+            ("class", class),
+            ("key", &convert_rust_object_to_java("key", key)?),
+            ("value", &convert_rust_object_to_java("value", value)?),
+            ("suffix", suffix),
+        ],
+    ))
+}
+
+pub fn get_kotlin_type(rust_type: &RustType) -> Result<String, String> {
+    get_kotlin_type_of(&rust_type.to_owned_type())
+}
+
+pub fn get_kotlin_input_type(rust_type: &RustType) -> Result<String, String> {
+    get_kotlin_type_of(&get_java_input_type(rust_type)?)
+}
+
+fn get_kotlin_type_of(rust_type: &RustType) -> Result<String, String> {
+    if let Some(primitive) = get_primitive(rust_type) {
+        return Ok(primitive.kotlin_type());
+    }
+    match rust_type {
+        _ if is_string(rust_type) => Ok("String".to_string()),
+        RustType::Vec(element) => Ok(match get_primitive(element) {
+            Some(primitive) => format!("{}Array", primitive.kotlin_type()),
+            None => format!("Array<{}>", get_kotlin_type_of(element)?),
+        }),
+        RustType::Map(_, key, value) => Ok(format!(
+            "Map<{}, {}>",
+            get_kotlin_type_of(key)?,
+            get_kotlin_type_of(value)?
+        )),
+        _ => Err(unsupported(rust_type)),
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::rust_type::parse_type;
     use rstest::rstest;
+    use serde_json::json;
 
     #[rstest]
     #[case("i8", "jbyte")]
@@ -78,8 +845,34 @@ mod tests {
     #[case("f64", "jdouble")]
     #[case("bool", "jboolean")]
     #[case("char", "jchar")]
+    #[case("String", "jstring")]
+    #[case("HashMap<String, f64>", "jobject")]
+    #[case("BTreeMap<String, i32>", "jobject")]
+    #[case("Vec<f32>", "jfloatArray")]
+    #[case("Vec<String>", "jobjectArray")]
+    #[case("Vec<Vec<f32>>", "jobjectArray")]
+    #[case("&str", "jstring")]
+    #[case("&[f32]", "jfloatArray")]
     fn test_get_jni_type(#[case] input: &str, #[case] expected: &str) {
-        assert_eq!(get_jni_type(input), expected);
+        assert_eq!(get_jni_type(&parse_type(input).unwrap()).unwrap(), expected);
+    }
+
+    #[rstest]
+    #[case("i32", "jint")]
+    #[case("String", "JString")]
+    #[case("HashMap<String, f64>", "JObject")]
+    #[case("Vec<bool>", "JBooleanArray")]
+    #[case("Vec<String>", "JObjectArray")]
+    #[case("&str", "JString")]
+    #[case("&[i64]", "JLongArray")]
+    #[case("&mut f32", "JFloatArray")]
+    #[case("&mut [u8]", "JByteArray")]
+    #[case("&mut [bool]", "JBooleanArray")]
+    fn test_get_jni_input_type(#[case] input: &str, #[case] expected: &str) {
+        assert_eq!(
+            get_jni_input_type(&parse_type(input).unwrap()).unwrap(),
+            expected
+        );
     }
 
     #[rstest]
@@ -95,13 +888,453 @@ mod tests {
     #[case("f64", "Double")]
     #[case("bool", "Boolean")]
     #[case("char", "Char")]
+    #[case("String", "String")]
+    #[case("HashMap<String, f64>", "Map<String, Double>")]
+    #[case("BTreeMap<String, i32>", "Map<String, Int>")]
+    #[case("HashMap<i64, bool>", "Map<Long, Boolean>")]
+    #[case("Vec<f32>", "FloatArray")]
+    #[case("Vec<String>", "Array<String>")]
+    #[case("Vec<Vec<f32>>", "Array<FloatArray>")]
+    #[case("Vec<Vec<Vec<u8>>>", "Array<Array<ByteArray>>")]
+    #[case("Vec<HashMap<String, Vec<i32>>>", "Array<Map<String, IntArray>>")]
+    #[case("&str", "String")]
+    #[case("&[f64]", "DoubleArray")]
+    #[case("&Vec<String>", "Array<String>")]
     fn test_get_kotlin_type(#[case] input: &str, #[case] expected: &str) {
-        assert_eq!(get_kotlin_type(input), expected);
+        assert_eq!(
+            get_kotlin_type(&parse_type(input).unwrap()).unwrap(),
+            expected
+        );
     }
 
     #[rstest]
-    #[should_panic(expected = "Unsupported type: unsupported")]
+    #[case("Vec<f32>", "[F")]
+    #[case("Vec<String>", "[Ljava/lang/String;")]
+    #[case("Vec<Vec<bool>>", "[[Z")]
+    #[case("BTreeMap<String, i32>", "Ljava/util/Map;")]
+    fn test_get_signature(#[case] input: &str, #[case] expected: &str) {
+        assert_eq!(
+            get_signature(&parse_type(input).unwrap()).unwrap(),
+            expected
+        );
+    }
+
+    #[rstest]
+    #[case("String", "java/lang/String")]
+    #[case("Vec<f32>", "[F")]
+    #[case("Vec<String>", "[Ljava/lang/String;")]
+    fn test_get_class_name(#[case] input: &str, #[case] expected: &str) {
+        assert_eq!(
+            get_class_name(&parse_type(input).unwrap()).unwrap(),
+            expected
+        );
+    }
+
+    #[test]
     fn test_get_jni_type_unsupported() {
-        get_jni_type("unsupported");
+        assert_eq!(
+            get_jni_type(&parse_type("unsupported").unwrap()),
+            Err("`unsupported` is not a supported type".to_string())
+        );
+    }
+
+    #[test]
+    fn test_get_kotlin_type_unsupported_element() {
+        assert_eq!(
+            get_kotlin_type(&parse_type("Vec<Point>").unwrap()),
+            Err("`Point` is not a supported type".to_string())
+        );
+    }
+
+    #[test]
+    fn test_convert_java_object_array_to_rust() {
+        let expected = r#"{
+    let length = env.get_array_length(&values).unwrap();
+    let mut result = Vec::with_capacity(length as usize);
+    for i in 0..length {
+        let element = env.get_object_array_element(&values, i).unwrap();
+        result.push({
+            let element = <&JFloatArray>::from(&element);
+            {
+                let length = env.get_array_length(&*element).unwrap();
+                let mut buffer = vec![jfloat::default(); length as usize];
+                env.get_float_array_region(&*element, 0, &mut buffer).unwrap();
+                buffer
+            }
+        });
+        env.delete_local_ref(element).unwrap();
+    }
+    result
+}"#;
+        assert_eq!(
+            convert_java_type_to_rust("values", &parse_type("Vec<Vec<f32>>").unwrap()).unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_convert_java_map_to_rust() {
+        let expected = r#"{
+    let map = JMap::from_env(&mut env, &values).unwrap();
+    let mut entries = map.iter(&mut env).unwrap();
+    let mut result = std::collections::HashMap::new();
+    while let Some((key, value)) = entries.next(&mut env).unwrap() {
+        result.insert(String::from(env.get_string(<&JString>::from(&key)).unwrap()), env.call_method(&value, "doubleValue", "()D", &[]).unwrap().d().unwrap());
+        env.delete_local_ref(key).unwrap();
+        env.delete_local_ref(value).unwrap();
+    }
+    result
+}"#;
+        assert_eq!(
+            convert_java_type_to_rust("values", &parse_type("HashMap<String, f64>").unwrap())
+                .unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_convert_rust_map_to_java() {
+        let expected = r#"{
+    let map = env.new_object("java/util/LinkedHashMap", "()V", &[]).unwrap();
+    for (key, value) in result {
+        let key = JObject::from(env.new_string(key).unwrap());
+        let value = env.new_object("java/lang/Integer", "(I)V", &[JValue::Int(value)]).unwrap();
+        env.call_method(
+            &map,
+            "put",
+            "(Ljava/lang/Object;Ljava/lang/Object;)Ljava/lang/Object;",
+            &[JValue::Object(&key), JValue::Object(&value)],
+        )
+        .unwrap();
+        env.delete_local_ref(key).unwrap();
+        env.delete_local_ref(value).unwrap();
+    }
+    map.into_raw()
+}"#;
+        assert_eq!(
+            convert_rust_type_to_java("result", &parse_type("BTreeMap<String, i32>").unwrap())
+                .unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_convert_java_primitive_array_to_rust() {
+        let expected = r#"{
+    let length = env.get_array_length(&values).unwrap();
+    let mut buffer = vec![jint::default(); length as usize];
+    env.get_int_array_region(&values, 0, &mut buffer).unwrap();
+    buffer.into_iter().map(|value| value as u32).collect()
+}"#;
+        assert_eq!(
+            convert_java_type_to_rust("values", &parse_type("Vec<u32>").unwrap()).unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_convert_rust_object_array_to_java() {
+        let expected = r#"{
+    let elements = result;
+    let array = env
+        .new_object_array(elements.len() as jsize, "[F", JObject::null())
+        .unwrap();
+    for (i, element) in elements.into_iter().enumerate() {
+        let element = JObject::from({
+            let buffer: Vec<jfloat> = element;
+            let array = env.new_float_array(buffer.len() as jsize).unwrap();
+            env.set_float_array_region(&array, 0, &buffer).unwrap();
+            array
+        });
+        env.set_object_array_element(&array, i as jsize, &element).unwrap();
+        env.delete_local_ref(element).unwrap();
+    }
+    array.into_raw()
+}"#;
+        assert_eq!(
+            convert_rust_type_to_java("result", &parse_type("Vec<Vec<f32>>").unwrap()).unwrap(),
+            expected
+        );
+    }
+
+    #[rstest]
+    #[case("&str", "&name_value")]
+    #[case("String", "String::from(env.get_string(&name).unwrap())")]
+    #[case("std::primitive::u8", "name as u8")]
+    #[case(
+        "char",
+        "char::from_u32(name.into()).unwrap_or(char::REPLACEMENT_CHARACTER)"
+    )]
+    fn test_convert_java_type_to_rust(#[case] input: &str, #[case] expected: &str) {
+        assert_eq!(
+            convert_java_type_to_rust("name", &parse_type(input).unwrap()).unwrap(),
+            expected
+        );
+    }
+
+    #[rstest]
+    #[case("&str", "env.new_string(result.to_owned()).unwrap().into_raw()")]
+    #[case("String", "env.new_string(result).unwrap().into_raw()")]
+    #[case("u16", "result as i16")]
+    #[case(
+        "Vec<u8>",
+        r#"{
+    let buffer: Vec<jbyte> = result.into_iter().map(|value| value as i8).collect();
+    let array = env.new_byte_array(buffer.len() as jsize).unwrap();
+    env.set_byte_array_region(&array, 0, &buffer).unwrap();
+    array.into_raw()
+}"#
+    )]
+    fn test_convert_rust_type_to_java(#[case] input: &str, #[case] expected: &str) {
+        assert_eq!(
+            convert_rust_type_to_java("result", &parse_type(input).unwrap()).unwrap(),
+            expected
+        );
+    }
+
+    #[rstest]
+    #[case("char", Some("u32::from(result) > 0xFFFF"))]
+    #[case("&char", Some("u32::from(*result) > 0xFFFF"))]
+    #[case(
+        "&[char]",
+        Some("result.iter().any(|value| u32::from(*value) > 0xFFFF)")
+    )]
+    #[case(
+        "Vec<Vec<char>>",
+        Some("result.iter().any(|value| value.iter().any(|value| u32::from(*value) > 0xFFFF))")
+    )]
+    #[case(
+        "HashMap<char, String>",
+        Some("result.iter().any(|(key, _)| u32::from(*key) > 0xFFFF)")
+    )]
+    #[case("HashMap<String, Vec<i32>>", None)]
+    #[case("String", None)]
+    fn test_get_wide_char_check(#[case] input: &str, #[case] expected: Option<&str>) {
+        assert_eq!(
+            get_wide_char_check("result", &parse_type(input).unwrap())
+                .unwrap()
+                .as_deref(),
+            expected
+        );
+    }
+
+    #[rstest]
+    #[case("f32", "Float")]
+    #[case("&str", "String")]
+    #[case("&mut f32", "FloatArray")]
+    #[case("&mut [f64]", "DoubleArray")]
+    #[case("&mut [i32]", "IntArray")]
+    fn test_get_kotlin_input_type(#[case] input: &str, #[case] expected: &str) {
+        assert_eq!(
+            get_kotlin_input_type(&parse_type(input).unwrap()).unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_get_kotlin_input_type_unsupported_mutable_reference() {
+        assert_eq!(
+            get_kotlin_input_type(&parse_type("&mut String").unwrap()),
+            Err("`&mut String` is not a supported type".to_string())
+        );
+    }
+
+    #[rstest]
+    #[case::primitive("u8", true, Ok(()))]
+    #[case::nested("Vec<HashMap<String, Vec<i32>>>", false, Ok(()))]
+    #[case::borrowed("&[String]", true, Ok(()))]
+    #[case::mutable_slice("&mut [f32]", true, Ok(()))]
+    #[case::mutable_scalar("&mut bool", true, Ok(()))]
+    #[case::mutable_string(
+        "&mut String",
+        true,
+        Err("only mutable references to primitives, or to slices of primitives, are supported")
+    )]
+    #[case::mutable_vec(
+        "&mut Vec<i32>",
+        true,
+        Err(
+            "mutable references to Vecs are not supported, since a Java array cannot change its length; use a mutable slice instead"
+        )
+    )]
+    #[case::mutable_output("&mut [f32]", false, Err("mutable references cannot be returned"))]
+    #[case::struct_type(
+        "Point",
+        false,
+        Err("`Point` is not a supported type, and structs and enums cannot be bound yet")
+    )]
+    #[case::struct_element(
+        "BTreeMap<String, Point>",
+        true,
+        Err("`Point` is not a supported type, and structs and enums cannot be bound yet")
+    )]
+    #[case::result_output("Result<Vec<i32>, String>", false, Ok(()))]
+    #[case::unit_result_output("Result<(), &'static str>", false, Ok(()))]
+    #[case::result_input("Result<i32, String>", true, Err("Results can only be returned"))]
+    #[case::nested_result(
+        "Result<Result<i32, String>, String>",
+        false,
+        Err("nested Results are not supported")
+    )]
+    fn test_check_type(
+        #[case] rust_type: &str,
+        #[case] is_input: bool,
+        #[case] expected: Result<(), &str>,
+    ) {
+        assert_eq!(
+            check_type(&parse_type(rust_type).unwrap(), is_input),
+            expected.map_err(str::to_string)
+        );
+    }
+
+    #[test]
+    fn test_mutable_scalar_input() {
+        let rust_type = parse_type("&mut u32").unwrap();
+        let expected_setup = r#"let mut count_value = {
+    let mut buffer = [jint::default(); 1];
+    env.get_int_array_region(&count, 0, &mut buffer).unwrap();
+    buffer[0] as u32
+};"#;
+        assert_eq!(
+            get_input_setup("count", &rust_type).unwrap().unwrap(),
+            expected_setup
+        );
+        assert_eq!(
+            convert_java_type_to_rust("count", &rust_type).unwrap(),
+            "&mut count_value"
+        );
+        assert_eq!(
+            get_input_write_back("count", &rust_type).unwrap().unwrap(),
+            "env.set_int_array_region(&count, 0, &[count_value as i32]).unwrap();"
+        );
+    }
+
+    #[test]
+    fn test_mutable_slice_input() {
+        let rust_type = parse_type("&mut [f32]").unwrap();
+        let expected_setup = r#"let mut values_value: Vec<f32> = {
+    let length = env.get_array_length(&values).unwrap();
+    let mut buffer = vec![jfloat::default(); length as usize];
+    env.get_float_array_region(&values, 0, &mut buffer).unwrap();
+    buffer
+};"#;
+        assert_eq!(
+            get_input_setup("values", &rust_type).unwrap().unwrap(),
+            expected_setup
+        );
+        assert_eq!(
+            get_input_write_back("values", &rust_type).unwrap().unwrap(),
+            "env.set_float_array_region(&values, 0, &values_value).unwrap();"
+        );
+    }
+
+    #[rstest]
+    #[case::string(
+        "&str",
+        "let name_value: String = String::from(env.get_string(&name).unwrap());"
+    )]
+    #[case::converted_elements(
+        "&[u8]",
+        r#"let name_value: Vec<u8> = {
+    let length = env.get_array_length(&name).unwrap();
+    let mut buffer = vec![jbyte::default(); length as usize];
+    env.get_byte_array_region(&name, 0, &mut buffer).unwrap();
+    buffer.into_iter().map(|value| value as u8).collect()
+};"#
+    )]
+    fn test_immutable_input_setup(#[case] input: &str, #[case] expected: &str) {
+        let rust_type = parse_type(input).unwrap();
+        assert_eq!(
+            get_input_setup("name", &rust_type).unwrap().unwrap(),
+            expected
+        );
+        assert_eq!(get_input_write_back("name", &rust_type).unwrap(), None);
+    }
+
+    #[test]
+    fn test_owned_input_has_no_setup() {
+        let rust_type = parse_type("Vec<f32>").unwrap();
+        assert_eq!(get_input_setup("values", &rust_type).unwrap(), None);
+    }
+
+    #[rstest]
+    #[case("f32", "F")]
+    #[case("&[f64]", "[D")]
+    #[case("&mut i32", "[I")]
+    #[case("&str", "Ljava/lang/String;")]
+    fn test_get_jni_input_signature(#[case] input: &str, #[case] expected: &str) {
+        assert_eq!(
+            get_jni_input_signature(&parse_type(input).unwrap()).unwrap(),
+            expected
+        );
+    }
+
+    #[rstest]
+    #[case("f32", "Default::default()")]
+    #[case("bool", "Default::default()")]
+    #[case("&str", "std::ptr::null_mut()")]
+    #[case("Vec<i32>", "std::ptr::null_mut()")]
+    fn test_get_jni_default_value(#[case] rust_type: &str, #[case] expected: &str) {
+        assert_eq!(
+            get_jni_default_value(&parse_type(rust_type).unwrap()),
+            expected
+        );
+    }
+
+    #[rstest]
+    #[case("i32", json!(-5), Ok("-5"))]
+    #[case("u8", json!(200), Ok("-56"))]
+    #[case("u8", json!(128), Ok("Byte.MIN_VALUE"))]
+    #[case("i64", json!(i64::MIN), Ok("Long.MIN_VALUE"))]
+    #[case("u64", json!(u64::MAX), Ok("-1"))]
+    #[case("i8", json!(128), Err("128 is out of the range of `i8`"))]
+    #[case("u32", json!(-1), Err("-1 is out of the range of `u32`"))]
+    #[case("i32", json!(1.5), Err("expected an integer, found 1.5"))]
+    #[case("f32", json!(1.5), Ok("1.5f"))]
+    #[case("f32", json!(2), Ok("2.0f"))]
+    #[case("f32", json!(1e300), Err("1e300 is out of the range of `f32`"))]
+    #[case("f32", json!(-3.5e38), Err("-3.5e38 is out of the range of `f32`"))]
+    #[case("f64", json!(0.1), Ok("0.1"))]
+    #[case("bool", json!(true), Ok("true"))]
+    #[case("bool", json!("true"), Err("expected true or false, found \"true\""))]
+    #[case("char", json!("'"), Ok(r"'\''"))]
+    #[case("char", json!("ab"), Err("expected a string of one character, found \"ab\""))]
+    #[case("char", json!("😀"), Err("'😀' does not fit in a Kotlin `Char`"))]
+    #[case("String", json!("a \"$b\"\n"), Ok(r#""a \"\$b\"\n""#))]
+    #[case("&str", json!("it's"), Ok(r#""it's""#))]
+    #[case("String", json!(1), Err("expected a string, found 1"))]
+    #[case("&mut i32", json!(1), Err("mutable references cannot have a default value"))]
+    #[case("Vec<i32>", json!([1]), Err("`Vec<i32>` cannot have a default value"))]
+    fn test_get_kotlin_default_value(
+        #[case] rust_type: &str,
+        #[case] value: Value,
+        #[case] expected: Result<&str, &str>,
+    ) {
+        assert_eq!(
+            get_kotlin_default_value(&parse_type(rust_type).unwrap(), &value),
+            expected.map(str::to_string).map_err(str::to_string)
+        );
+    }
+
+    /// An unsigned default above the signed maximum is cast back to the value of the config,
+    /// instead of failing a checked conversion.
+    #[rstest]
+    #[case::u8("u8", 200, "-56", "v as u8")]
+    #[case::u64("u64", u64::MAX, "-1", "v as u64")]
+    fn test_unsigned_default_round_trip(
+        #[case] rust_type: &str,
+        #[case] value: u64,
+        #[case] kotlin: &str,
+        #[case] conversion: &str,
+    ) {
+        let rust_type = parse_type(rust_type).unwrap();
+        assert_eq!(
+            get_kotlin_default_value(&rust_type, &json!(value)).unwrap(),
+            kotlin
+        );
+        assert_eq!(
+            convert_java_type_to_rust("v", &rust_type).unwrap(),
+            conversion
+        );
     }
 }